@@ -0,0 +1,54 @@
+/// How a sliding tile speeds up and slows down on its way between slots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    /// The same speed the whole way.
+    Linear,
+    /// Starts fast and slows down into the slot.
+    EaseOut,
+    /// Overshoots the slot a little and settles back into it.
+    Spring,
+}
+
+impl Easing {
+    /// How quickly the spring's overshoot dies away.
+    const SPRING_DAMPING: f32 = 8.0;
+    /// How quickly the spring swings back and forth, in radians over the whole animation.
+    const SPRING_FREQUENCY: f32 = 10.0;
+
+    /// How far along its way a tile is, as a fraction of the distance, once the given fraction of the animation's
+    /// time has passed. Both start at 0 and end at 1, though a spring passes 1 along the way.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::Spring => {
+                1.0 - (-t * Easing::SPRING_DAMPING).exp() * (t * Easing::SPRING_FREQUENCY).cos()
+            }
+        }
+    }
+
+    /// The easing after this one, going round them all in turn.
+    pub fn next(self) -> Easing {
+        match self {
+            Easing::Linear => Easing::EaseOut,
+            Easing::EaseOut => Easing::Spring,
+            Easing::Spring => Easing::Linear,
+        }
+    }
+}
+
+#[test]
+fn easing() {
+    for easing in [Easing::Linear, Easing::EaseOut, Easing::Spring].iter() {
+        assert_eq!(easing.apply(0.0), 0.0);
+        assert!((easing.apply(1.0) - 1.0).abs() < 0.001, "{:?} should end in the slot", easing);
+        assert_eq!(easing.apply(2.0), easing.apply(1.0));
+    }
+
+    assert_eq!(Easing::Linear.apply(0.5), 0.5);
+    assert!(Easing::EaseOut.apply(0.5) > 0.5);
+    assert!((0..=100).any(|t| Easing::Spring.apply(t as f32 / 100.0) > 1.0));
+
+    assert_eq!(Easing::Linear.next().next().next(), Easing::Linear);
+}
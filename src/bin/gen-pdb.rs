@@ -0,0 +1,61 @@
+//! Generates the pattern databases the solvers use for a size of square board, and caches them in the `pdb` directory
+//! under the current directory, where the game and the terminal front-end find them when run from the same place.
+//! Needs nothing from Amethyst, so it builds with any of the game's features.
+
+#![warn(rust_2018_idioms, clippy::all)]
+
+use fresh::pdb::{self, PatternDatabases};
+
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "\
+Usage:
+    gen-pdb <dim>            generate the pattern databases for <dim>x<dim> boards";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let dim = match parse(&args) {
+        Ok(Some(dim)) => dim,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => fail(&err),
+    };
+
+    let partition = PatternDatabases::partition(dim)
+        .unwrap_or_else(|| fail(&format!("There are no pattern databases for {}x{} boards", dim, dim)));
+    println!("Generating the pattern databases for {}x{} boards, this takes a while", dim, dim);
+
+    let path = PatternDatabases::path(Path::new(pdb::DIRECTORY), dim);
+    if let Err(err) = PatternDatabases::generate(dim, &partition).save(&path) {
+        fail(&format!("Couldn't save the pattern databases to {}: {}", path.display(), err));
+    }
+    println!("Saved the pattern databases to {}", path.display());
+}
+
+fn fail(err: &str) -> ! {
+    eprintln!("{}", err);
+    process::exit(1)
+}
+
+/// Reads the board dimension from the arguments, without the program name, or `None` if help was asked for.
+fn parse(args: &[String]) -> Result<Option<u32>, String> {
+    match args {
+        [arg] if arg == "--help" => Ok(None),
+        [dim] => dim.parse().map(Some).map_err(|_| format!("\"{}\" isn't a board dimension\n\n{}", dim, USAGE)),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+#[test]
+fn parse_args() {
+    let parse = |args: &str| parse(&args.split_whitespace().map(str::to_string).collect::<Vec<_>>());
+
+    assert_eq!(parse("4"), Ok(Some(4)));
+    assert_eq!(parse("--help"), Ok(None));
+    assert!(parse("").is_err());
+    assert!(parse("four").is_err());
+    assert!(parse("4 5").is_err());
+}
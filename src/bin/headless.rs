@@ -0,0 +1,144 @@
+//! Plays the puzzle without a window, for scripted runs and checking puzzle files. Starts from a puzzle file or a
+//! scrambled board, makes the moves read from a file or stdin, then prints the board, whether it's solved and how many
+//! moves were made. Needs nothing from Amethyst, so it builds with any of the game's features.
+
+#![warn(rust_2018_idioms, clippy::all)]
+
+use fresh::puzzle::{Direction, Puzzle};
+use fresh::stats::MoveMetric;
+
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+mod setup;
+
+/// The usage message, followed by `setup::OPTIONS`.
+const USAGE: &str = "\
+Usage:
+    headless [options] [moves file]   make the moves in the file, or on stdin without one, and print the board
+
+Moves are separated by whitespace. up, down, left and right slide the tile that can move that way, and a slot number
+slides the line of tiles from that slot to the empty slot, slots counting from 0 in the bottom left. Anything after a
+# on a line is ignored. Boards are scrambled with seed 0 unless another is given.
+
+Options:";
+
+/// A move read from the script.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Move {
+    /// Slide the tile that can move in the direction.
+    Towards(Direction),
+    /// Slide the line of tiles from the slot up to the empty slot.
+    Line(u32),
+}
+
+/// How many moves were made, counted both ways the game can count them.
+#[derive(Debug, Default, PartialEq)]
+struct Played {
+    tiles: u32,
+    lines: u32,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let usage = format!("{}\n{}", USAGE, setup::OPTIONS);
+    let (setup, rest) = match setup::parse(&args, &usage) {
+        Ok(Some(parsed)) => parsed,
+        Ok(None) => {
+            println!("{}", usage);
+            return;
+        }
+        Err(err) => fail(&err),
+    };
+    if rest.len() > 1 {
+        fail(&format!("Only one moves file can be given\n\n{}", usage));
+    }
+
+    if let Err(err) = run(&setup, rest.first()) {
+        fail(&err);
+    }
+}
+
+fn fail(err: &str) -> ! {
+    eprintln!("{}", err);
+    process::exit(1)
+}
+
+/// Plays the moves in the file, or on stdin without one, on the board set up.
+fn run(setup: &setup::Setup, moves: Option<&String>) -> Result<(), String> {
+    let mut puzzle = setup.puzzle(0)?;
+
+    let mut script = String::new();
+    match moves {
+        Some(path) => script = fs::read_to_string(path).map_err(|err| format!("Couldn't read {}: {}", path, err))?,
+        None => {
+            io::stdin()
+                .read_to_string(&mut script)
+                .map_err(|err| format!("Couldn't read the moves: {}", err))?;
+        }
+    }
+
+    let played = play(&mut puzzle, &script)?;
+    print!("{}", puzzle);
+    println!("Solved: {}", if puzzle.is_solved() { "yes" } else { "no" });
+    println!("Moves: {}", played.tiles);
+    println!("Line moves: {}", played.lines);
+    Ok(())
+}
+
+/// Reads the moves in a script, see `USAGE`. The error says which one couldn't be read.
+fn parse_moves(script: &str) -> Result<Vec<Move>, String> {
+    script
+        .lines()
+        .flat_map(|line| line.split('#').next().unwrap_or("").split_whitespace())
+        .map(|word| match word.to_ascii_lowercase().as_str() {
+            "up" => Ok(Move::Towards(Direction::Up)),
+            "down" => Ok(Move::Towards(Direction::Down)),
+            "left" => Ok(Move::Towards(Direction::Left)),
+            "right" => Ok(Move::Towards(Direction::Right)),
+            _ => word.parse().map(Move::Line).map_err(|_| format!("\"{}\" isn't a move", word)),
+        })
+        .collect()
+}
+
+/// Makes the moves in the script on the puzzle. Stops at the first move that can't be made, saying which it was.
+fn play(puzzle: &mut Puzzle, script: &str) -> Result<Played, String> {
+    let mut played = Played::default();
+    for (i, next) in parse_moves(script)?.into_iter().enumerate() {
+        let line = match next {
+            Move::Towards(direction) => puzzle.tile_moving(direction).map(|slot| vec![slot]),
+            Move::Line(slot) => puzzle.line_to_empty(slot),
+        };
+        let line = line.ok_or_else(|| {
+            format!("Move {} ({:?}) can't be made on\n{}", i + 1, next, puzzle.to_string().trim_end())
+        })?;
+
+        for slot in &line {
+            puzzle.move_tile_at(*slot);
+        }
+        played.tiles += MoveMetric::Tiles.count(line.len());
+        played.lines += MoveMetric::Lines.count(line.len());
+    }
+    Ok(played)
+}
+
+#[test]
+fn play_script() {
+    // + - - - +
+    // | 6 7 8 |
+    // | 3 4 5 |
+    // | 1 2 _ |
+    // + - - - +
+    let mut puzzle = Puzzle::from_text("6 7 8\n3 4 5\n1 2 0").unwrap();
+    let played = play(&mut puzzle, "Right left\n# Put the empty slot home.\n0\n").unwrap();
+    assert_eq!(played, Played { tiles: 4, lines: 3 });
+    assert!(puzzle.is_solved());
+
+    assert_eq!(parse_moves("up 3 # left"), Ok(vec![Move::Towards(Direction::Up), Move::Line(3)]));
+    assert_eq!(parse_moves("up sideways"), Err("\"sideways\" isn't a move".to_string()));
+
+    let err = play(&mut puzzle, "down up up").unwrap_err();
+    assert!(err.starts_with("Move 3 (Towards(Up)) can't be made on\n"), "{}", err);
+    assert!(play(&mut puzzle, "4").is_err());
+}
@@ -0,0 +1,108 @@
+//! Setting up a board from the command line, shared by the binaries that play without Amethyst.
+
+use fresh::options;
+use fresh::puzzle::{Difficulty, Puzzle};
+
+use std::fs;
+use std::path::PathBuf;
+
+/// The options `parse` understands, for the binaries' usage messages.
+pub const OPTIONS: &str = "\
+    --puzzle <file>          start from a puzzle: a row of tile numbers per line from the top, 0 for the empty slot
+    --width <tiles>          how many tiles wide the scrambled board is, 2 to 10, 4 unless given
+    --height <tiles>         how many tiles high the scrambled board is, 2 to 10, 4 unless given
+    --seed <number>          the seed to scramble the board with
+    --difficulty <name>      how hard to scramble the board: easy, medium or random, easy unless given
+    --help                   show this message";
+
+/// Where to start from, as given on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setup {
+    /// A puzzle file to start from, rather than scrambling a board.
+    pub puzzle: Option<PathBuf>,
+    pub tiles_width: u32,
+    pub tiles_height: u32,
+    pub seed: Option<u64>,
+    pub difficulty: Option<Difficulty>,
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Setup {
+            puzzle: None,
+            tiles_width: 4,
+            tiles_height: 4,
+            seed: None,
+            difficulty: Some(Difficulty::EASY),
+        }
+    }
+}
+
+impl Setup {
+    /// The puzzle file, checked to be one that can be played, or else a board scrambled with the seed given, or
+    /// `default_seed` without one. The error says what's wrong with the puzzle file.
+    pub fn puzzle(&self, default_seed: u64) -> Result<Puzzle, String> {
+        let path = match &self.puzzle {
+            Some(path) => path,
+            None => {
+                let seed = self.seed.unwrap_or(default_seed);
+                return Ok(Puzzle::scrambled(self.tiles_width, self.tiles_height, seed, self.difficulty));
+            }
+        };
+
+        let text = fs::read_to_string(path).map_err(|err| format!("Couldn't read {}: {}", path.display(), err))?;
+        let puzzle = Puzzle::from_text(&text).map_err(|err| format!("{} isn't a puzzle, {}", path.display(), err))?;
+        if !puzzle.is_permutation() {
+            return Err(format!("{} doesn't hold every tile exactly once", path.display()));
+        }
+        if !puzzle.is_solvable() {
+            return Err(format!("{} can't be solved", path.display()));
+        }
+        Ok(puzzle)
+    }
+}
+
+/// Parses the command line arguments, without the program name, into the setup and any other arguments, or `None` if
+/// help was asked for. The error says what's wrong with them, followed by `usage`.
+pub fn parse(args: &[String], usage: &str) -> Result<Option<(Setup, Vec<String>)>, String> {
+    let parsed = match options::parse(args, usage)? {
+        Some(parsed) => parsed,
+        None => return Ok(None),
+    };
+
+    let mut setup = Setup::default();
+    for (flag, value) in parsed.flags {
+        match flag.as_str() {
+            "--puzzle" => setup.puzzle = Some(value.into()),
+            "--width" => setup.tiles_width = options::tiles(&flag, &value)?,
+            "--height" => setup.tiles_height = options::tiles(&flag, &value)?,
+            "--seed" => setup.seed = Some(options::seed(&value)?),
+            "--difficulty" => setup.difficulty = options::difficulty(&value)?,
+            _ => return Err(format!("Unknown option {}\n\n{}", flag, usage)),
+        }
+    }
+
+    Ok(Some((setup, parsed.rest)))
+}
+
+#[test]
+fn parse_args() {
+    let parse = |args: &str| parse(&args.split_whitespace().map(str::to_string).collect::<Vec<_>>(), "usage");
+
+    assert_eq!(parse(""), Ok(Some((Setup::default(), vec![]))));
+    assert_eq!(
+        parse("--width=3 --height 2 --seed 7 --difficulty random moves.txt"),
+        Ok(Some((
+            Setup {
+                tiles_width: 3,
+                tiles_height: 2,
+                seed: Some(7),
+                difficulty: None,
+                ..Setup::default()
+            },
+            vec!["moves.txt".to_string()]
+        )))
+    );
+
+    assert_eq!(parse("--colour blue"), Err("Unknown option --colour\n\nusage".to_string()));
+}
@@ -0,0 +1,382 @@
+//! Plays the puzzle in a terminal, with the board drawn as a grid of tile numbers. Makes moves, undoes and redoes them
+//! and gives hints the same way the game does, through the same puzzle, history and solvers, so it works over SSH and
+//! on machines without a display. Needs the `tui` feature, but nothing from Amethyst.
+
+#![warn(rust_2018_idioms, clippy::all)]
+
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use fresh::constructive;
+use fresh::history::{History, Slide};
+use fresh::pdb::{self, PatternDatabases};
+use fresh::puzzle::{Direction, Puzzle};
+use fresh::solver;
+use fresh::stats::{format_duration, MoveMetric, Stats};
+
+use std::io::{self, Write};
+use std::path::Path;
+use std::process;
+use std::time::{Duration, Instant};
+
+mod setup;
+
+/// The usage message, followed by `setup::OPTIONS`.
+const USAGE: &str = "\
+Usage:
+    tui [options]            play in the terminal
+
+Boards are scrambled with a random seed unless one is given.
+
+Options:";
+
+/// The keys, shown under the board.
+const CONTROLS: &str = "Arrows/WASD: move  U: undo  R: redo  H: hint  N: new board  Q: quit";
+
+/// How moves are counted, as the game does unless the player switches.
+const METRIC: MoveMetric = MoveMetric::Tiles;
+/// How hard the optimal solver tries before falling back to the constructive solver's longer solution.
+const SOLVER_NODE_BUDGET: u64 = 200_000;
+/// How long to wait for a key before redrawing the clock.
+const TICK: Duration = Duration::from_millis(250);
+
+/// What a key asks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Towards(Direction),
+    Undo,
+    Redo,
+    Hint,
+    NewBoard,
+    Quit,
+}
+
+/// A board being played, with everything the game keeps in the world alongside it.
+struct Game {
+    puzzle: Puzzle,
+    history: History,
+    stats: Stats,
+    /// The slot of the tile the last hint picked, until the board changes.
+    hint: Option<u32>,
+    /// Pattern databases for the board's dimension, if they've been generated.
+    databases: Option<PatternDatabases>,
+}
+
+impl Game {
+    fn new(puzzle: Puzzle) -> Game {
+        let databases = load_pattern_databases(&puzzle);
+        Game {
+            puzzle,
+            history: History::default(),
+            stats: Stats::default(),
+            hint: None,
+            databases,
+        }
+    }
+
+    /// Starts again on another board the same size, keeping the pattern databases.
+    fn restart(&mut self, puzzle: Puzzle) {
+        self.puzzle = puzzle;
+        self.history = History::default();
+        self.stats = Stats::default();
+        self.hint = None;
+    }
+
+    /// Does what the key asked for. Once the board's solved only a new board can be asked for.
+    fn act(&mut self, action: Action) {
+        if self.puzzle.is_solved() {
+            return;
+        }
+
+        match action {
+            Action::Towards(direction) => {
+                if let Some(from) = self.puzzle.tile_moving(direction) {
+                    self.slide(from);
+                }
+            }
+            Action::Undo => {
+                if let Some(slides) = self.history.next_undo() {
+                    self.play(&slides);
+                    self.history.undo();
+                    self.stats.moves -= METRIC.count(slides.len());
+                }
+            }
+            Action::Redo => {
+                if let Some(slides) = self.history.next_redo() {
+                    self.play(&slides);
+                    self.history.redo();
+                    self.stats.moves += METRIC.count(slides.len());
+                }
+            }
+            Action::Hint => self.hint(),
+            Action::NewBoard | Action::Quit => {}
+        }
+
+        if self.puzzle.is_solved() {
+            self.stats.timing = false;
+        }
+    }
+
+    /// Slides the line of tiles from the given slot up to the empty slot, if it's in line with it.
+    fn slide(&mut self, from: u32) {
+        if let Some(slides) = Slide::line(&self.puzzle, from) {
+            self.play(&slides);
+            self.stats.moves += METRIC.count(slides.len());
+            self.stats.timing = true;
+            self.history.record(slides);
+        }
+    }
+
+    fn play(&mut self, slides: &[Slide]) {
+        for slide in slides {
+            self.puzzle.move_tile_at(slide.from);
+        }
+        self.hint = None;
+    }
+
+    /// The first press picks a tile to move and counts against the player's score, pressing again moves it.
+    fn hint(&mut self) {
+        if let Some(from) = self.hint {
+            self.slide(from);
+            return;
+        }
+
+        let limits = solver::Limits {
+            node_budget: Some(SOLVER_NODE_BUDGET),
+            cancel: None,
+        };
+        let solution = solver::solve(&self.puzzle, self.databases.as_ref(), &limits)
+            .or_else(|_| constructive::solve(&self.puzzle))
+            .ok();
+        self.hint = solution.and_then(|moves| moves.first().copied());
+        if self.hint.is_some() {
+            self.stats.hints += 1;
+        }
+    }
+
+    /// The lines shown under the board.
+    fn status(&self) -> Vec<String> {
+        let stats = &self.stats;
+        let progress = format!(
+            "Moves: {}  Hints: {}  Time: {}",
+            stats.moves,
+            stats.hints,
+            format_duration(stats.elapsed)
+        );
+        if self.puzzle.is_solved() {
+            vec![
+                progress,
+                format!("Solved! Score {}. N for a new board, Q to quit.", stats.score()),
+            ]
+        } else {
+            vec![progress, CONTROLS.to_string()]
+        }
+    }
+}
+
+/// Loads the cached pattern databases for the board's dimension from the `pdb` directory under the current directory,
+/// if they've been generated. Without them hints are just slower to find.
+fn load_pattern_databases(puzzle: &Puzzle) -> Option<PatternDatabases> {
+    let dim = puzzle.tiles_width();
+    if puzzle.tiles_height() != dim {
+        return None;
+    }
+    PatternDatabases::load(&PatternDatabases::path(Path::new(pdb::DIRECTORY), dim), dim).ok()
+}
+
+/// Puts the terminal into raw mode on its own screen, and back how it was when dropped, even if the game panics.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter(out: &mut impl Write) -> crossterm::Result<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        execute!(out, EnterAlternateScreen, Hide)?;
+        Ok(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let usage = format!("{}\n{}", USAGE, setup::OPTIONS);
+    let (setup, rest) = match setup::parse(&args, &usage) {
+        Ok(Some(parsed)) => parsed,
+        Ok(None) => {
+            println!("{}", usage);
+            return;
+        }
+        Err(err) => fail(&err),
+    };
+    if let Some(arg) = rest.first() {
+        fail(&format!("Unexpected argument {}\n\n{}", arg, usage));
+    }
+
+    let puzzle = setup.puzzle(rand::random()).unwrap_or_else(|err| fail(&err));
+    if let Err(err) = run(Game::new(puzzle), &setup) {
+        fail(&err.to_string());
+    }
+}
+
+fn fail(err: &str) -> ! {
+    eprintln!("{}", err);
+    process::exit(1)
+}
+
+/// Plays boards until the player quits.
+fn run(mut game: Game, setup: &setup::Setup) -> crossterm::Result<()> {
+    let mut out = io::stdout();
+    let _terminal = RawTerminal::enter(&mut out)?;
+
+    let mut last_tick = Instant::now();
+    // Redrawing clears the screen, which flickers, so it's only done when something's changed.
+    let mut changed = true;
+    let mut shown_status = vec![];
+    loop {
+        if changed || game.status() != shown_status {
+            draw(&mut out, &game)?;
+            shown_status = game.status();
+            changed = false;
+        }
+
+        if event::poll(TICK)? {
+            if let Event::Key(key) = event::read()? {
+                changed = true;
+                match action(key) {
+                    Some(Action::Quit) => return Ok(()),
+                    Some(Action::NewBoard) => {
+                        let (tiles_width, tiles_height) = (game.puzzle.tiles_width(), game.puzzle.tiles_height());
+                        game.restart(Puzzle::scrambled(tiles_width, tiles_height, rand::random(), setup.difficulty));
+                    }
+                    Some(action) => game.act(action),
+                    None => {}
+                }
+            }
+        }
+
+        let now = Instant::now();
+        game.stats.tick(now - last_tick);
+        last_tick = now;
+    }
+}
+
+/// What the key asks for, if anything.
+fn action(key: KeyEvent) -> Option<Action> {
+    // Raw mode stops Ctrl+C from interrupting, so it quits like Q.
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+        return Some(Action::Quit);
+    }
+
+    let action = match key.code {
+        KeyCode::Up | KeyCode::Char('w') => Action::Towards(Direction::Up),
+        KeyCode::Down | KeyCode::Char('s') => Action::Towards(Direction::Down),
+        KeyCode::Left | KeyCode::Char('a') => Action::Towards(Direction::Left),
+        KeyCode::Right | KeyCode::Char('d') => Action::Towards(Direction::Right),
+        KeyCode::Char('u') | KeyCode::Char('z') => Action::Undo,
+        KeyCode::Char('r') | KeyCode::Char('y') => Action::Redo,
+        KeyCode::Char('h') => Action::Hint,
+        KeyCode::Char('n') => Action::NewBoard,
+        KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// Draws the board as a grid of tile numbers, with the hinted tile picked out, and the status under it.
+fn draw(out: &mut impl Write, game: &Game) -> crossterm::Result<()> {
+    let puzzle = &game.puzzle;
+    // Room for the biggest tile number with a space either side.
+    let cell = puzzle.slots().saturating_sub(1).to_string().len() + 2;
+    let border = format!("+{}+", "-".repeat(cell * puzzle.tiles_width() as usize));
+
+    queue!(out, Clear(ClearType::All), MoveTo(0, 0), Print(&border), Print("\r\n"))?;
+    // Slots count up from the bottom row.
+    for y in (0..puzzle.tiles_height()).rev() {
+        queue!(out, Print("|"))?;
+        for x in 0..puzzle.tiles_width() {
+            let slot = puzzle.xy_idx((x, y));
+            let text = match puzzle.tile_at(slot) {
+                Some(tile) => format!("{:^width$}", tile, width = cell),
+                None => " ".repeat(cell),
+            };
+            if game.hint == Some(slot) {
+                queue!(out, SetAttribute(Attribute::Reverse), Print(text), SetAttribute(Attribute::Reset))?;
+            } else {
+                queue!(out, Print(text))?;
+            }
+        }
+        queue!(out, Print("|\r\n"))?;
+    }
+    queue!(out, Print(&border), Print("\r\n\r\n"))?;
+
+    for line in game.status() {
+        queue!(out, Print(line), Print("\r\n"))?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[test]
+fn play() {
+    // + - - - +
+    // | 6 7 8 |
+    // | 3 4 5 |
+    // | 1 2 _ |
+    // + - - - +
+    let mut game = Game::new(Puzzle::from_text("6 7 8\n3 4 5\n1 2 0").unwrap());
+    let start = game.puzzle.tiles().to_vec();
+
+    game.act(Action::Towards(Direction::Right));
+    game.act(Action::Towards(Direction::Up));
+    assert_eq!(game.stats.moves, 1);
+    assert!(game.stats.timing);
+
+    game.act(Action::Undo);
+    assert_eq!(game.puzzle.tiles(), &start[..]);
+    assert_eq!(game.stats.moves, 0);
+    game.act(Action::Redo);
+    game.act(Action::Undo);
+
+    // The first press picks the tile, the second moves it. Two tiles from solved, so two hints solve it.
+    game.act(Action::Hint);
+    assert_eq!(game.hint, Some(1));
+    game.act(Action::Hint);
+    game.act(Action::Hint);
+    game.act(Action::Hint);
+    assert!(game.puzzle.is_solved());
+    assert_eq!((game.stats.moves, game.stats.hints), (2, 2));
+    assert!(!game.stats.timing);
+    let score = 2 + 2 * Stats::HINT_PENALTY;
+    assert_eq!(game.status()[1], format!("Solved! Score {}. N for a new board, Q to quit.", score));
+
+    // Nothing but a new board once it's solved.
+    game.act(Action::Undo);
+    assert!(game.puzzle.is_solved());
+
+    game.restart(Puzzle::scrambled(3, 3, 1, None));
+    assert!(!game.puzzle.is_solved());
+    assert_eq!(game.stats, Stats::default());
+    assert_eq!(game.status(), vec!["Moves: 0  Hints: 0  Time: 0:00".to_string(), CONTROLS.to_string()]);
+}
+
+#[test]
+fn keys() {
+    let key = |code| KeyEvent { code, modifiers: KeyModifiers::NONE };
+    assert_eq!(action(key(KeyCode::Up)), Some(Action::Towards(Direction::Up)));
+    assert_eq!(action(key(KeyCode::Char('a'))), Some(Action::Towards(Direction::Left)));
+    assert_eq!(action(key(KeyCode::Char('h'))), Some(Action::Hint));
+    assert_eq!(action(key(KeyCode::Char('x'))), None);
+    assert_eq!(
+        action(KeyEvent { code: KeyCode::Char('c'), modifiers: KeyModifiers::CONTROL }),
+        Some(Action::Quit)
+    );
+}
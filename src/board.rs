@@ -2,95 +2,164 @@ use super::components::*;
 use amethyst::core::math::geometry::Point3;
 use amethyst::core::math::Vector3;
 use amethyst::prelude::*;
-use amethyst::{assets::*, core::*, ecs::Entity, renderer::*};
-
-pub type TileId = u32;
-
-///
-/// Slots numbers:
-/// + - - +
-/// | 2 3 | 1
-/// | 0 1 | 0
-/// + - - + y
-/// x 0 1
-///
+use amethyst::{
+    assets::*,
+    core::*,
+    ecs::{Entity, Join},
+    renderer::{loaders::load_from_srgba, palette::Srgba, *},
+    ui::{Anchor, FontAsset, LineMode, TtfFormat, UiText, UiTransform},
+    utils::application_root_dir,
+};
+use fresh::puzzle::{Difficulty, Puzzle, TileId};
+use serde::{Deserialize, Serialize};
+
+use std::path::PathBuf;
+
+use super::image_size;
+use super::menu::default_font;
+
+/// The gap left around the board when fitting it to the screen, as a fraction of the screen's shorter side.
+const MARGIN: f32 = 0.05;
+
+/// The image boards are cut from unless another is picked, within the assets directory.
+pub const IMAGE: &str = "background.jpg";
+
+/// Where an image is found: images are named within the assets directory, or given by an absolute path. `None` if
+/// the application root can't be found.
+pub fn image_path(image: &str) -> Option<PathBuf> {
+    application_root_dir().ok().map(|root| root.join("assets").join(image))
+}
+
+/// The number of tiles across and down a board cut from an image of the given size, without losing much of the image,
+/// with `tiles` along the image's longer side and at least `min_tiles` along the other.
+pub fn shape_for_image((image_width, image_height): (u32, u32), tiles: u32, min_tiles: u32) -> (u32, u32) {
+    let (long, short) = (image_width.max(image_height), image_width.min(image_height));
+    let short_tiles = (tiles as f32 * short as f32 / long as f32).round().max(min_tiles as f32) as u32;
+    if image_width >= image_height {
+        (tiles, short_tiles)
+    } else {
+        (short_tiles, tiles)
+    }
+}
+
+/// How much of its slot a numbered tile covers across, leaving a gap between it and its neighbours.
+const NUMBERED_TILE_FILL: f32 = 0.94;
+
+/// How numbered tiles look, loaded from `config/numbers.ron`. Anything left out of the file keeps its default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NumberStyle {
+    /// A TrueType font within the assets directory to number the tiles in, or `None` for Amethyst's default font.
+    pub font: Option<String>,
+    /// The colour of the tiles, as red, green, blue and alpha from 0 to 1.
+    pub tile_colour: [f32; 4],
+    /// The colour of the numbers on them.
+    pub text_colour: [f32; 4],
+    /// How tall the numbers are, as a fraction of a tile.
+    pub text_size: f32,
+}
+
+impl Default for NumberStyle {
+    fn default() -> Self {
+        NumberStyle {
+            font: None,
+            tile_colour: [0.93, 0.87, 0.75, 1.0],
+            text_colour: [0.2, 0.17, 0.14, 1.0],
+            text_size: 0.5,
+        }
+    }
+}
+
+/// Shows a `Puzzle` in the world: sizes it to the screen, cuts its tiles from an image or numbers them and works out
+/// where each slot is. Lives in the world as a resource, and every change to the puzzle goes through `puzzle_mut`.
 #[derive(Debug)]
 pub struct Board {
-    tiles_dim: u32,
-    tiles: Vec<Option<TileId>>,
+    puzzle: Puzzle,
+    /// The length of the board's longer side in world units. Tiles are square.
     board_size: f32,
 }
 
 impl Board {
-    /// Creates and returns an entity representing the board with child entities representing the tiles on the board.
-    /// Also adds a Board to the world's storage.
-    pub fn init_board(tiles_dim: u32, screen_size: u32, world: &mut World) -> Entity {
-        let board = {
-            let num_tiles = tiles_dim * tiles_dim;
-            let mut tiles = Vec::with_capacity((num_tiles) as usize);
-            tiles.push(None);
-            for i in 1..num_tiles {
-                tiles.push(Some(i));
-            }
+    /// Creates and returns an entity representing the board with child entities representing the tiles on the board,
+    /// cut from the given image, or numbered without one. Also adds a Board to the world's storage. The board's
+    /// scrambled as by `Puzzle::scrambled`.
+    pub fn init_board(
+        tiles_width: u32,
+        tiles_height: u32,
+        (screen_width, screen_height): (f32, f32),
+        seed: u64,
+        difficulty: Option<Difficulty>,
+        image: Option<&str>,
+        world: &mut World,
+    ) -> Entity {
+        let board = Board::new(Puzzle::scrambled(tiles_width, tiles_height, seed, difficulty));
 
-            let mut board = Board {
-                tiles_dim,
-                tiles,
-                board_size: screen_size as f32,
-            };
+        board.add_to_world((screen_width, screen_height), image, world)
+    }
 
-            board.scramble();
-            board
-        };
+    /// Fits the board to the screen, then adds it to the world the same way as `init_board`, with its tiles cut from
+    /// the given image, or numbered without one. Returns the board's entity.
+    pub fn add_to_world(
+        mut self,
+        (screen_width, screen_height): (f32, f32),
+        image: Option<&str>,
+        world: &mut World,
+    ) -> Entity {
+        self.fit(screen_width, screen_height);
 
-        let ret = board.create_entity(world);
+        let ret = self.create_entity(world, image);
 
-        world.insert(board);
+        world.insert(self);
 
         ret
     }
 
-    fn create_entity(&self, world: &mut World) -> Entity {
-        let sprite_sheet = self.load_sprite_sheet(world, "background.jpg");
+    /// Shows the given puzzle, still to be fitted to the screen.
+    pub fn new(puzzle: Puzzle) -> Board {
+        Board { puzzle, board_size: 0.0 }
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzle
+    }
+
+    /// The puzzle, to make moves on. Tiles already in the world aren't moved to match.
+    pub fn puzzle_mut(&mut self) -> &mut Puzzle {
+        &mut self.puzzle
+    }
+
+    fn create_entity(&self, world: &mut World, image: Option<&str>) -> Entity {
+        let sprite_sheet = self.load_faces(world, image);
         let transform = Transform::default();
         let board = world.create_entity().with(transform).named("Board").build();
 
-        for (idx, tile) in self.tiles.iter().enumerate() {
+        for (idx, tile) in self.puzzle.tiles().iter().enumerate() {
             if let Some(tile_id) = tile {
-                self.init_tile(world, sprite_sheet.clone(), *tile_id, idx as u32, board);
+                let tile = self.init_tile(world, sprite_sheet.clone(), image.is_some(), *tile_id, idx as u32, board);
+                if image.is_none() {
+                    Board::init_number(world, tile, *tile_id);
+                }
             }
         }
 
         board
     }
 
+    /// Creates a tile showing its piece of the sprite sheet if `cut` is set, or else the sheet's only sprite.
     fn init_tile(
         &self,
         world: &mut World,
         sprite_sheet: Handle<SpriteSheet>,
-        tile_id: u32,
+        cut: bool,
+        tile_id: TileId,
         index: u32,
         parent: Entity,
     ) -> Entity {
-        let (x, y) = self.idx_xy(index);
-
-        let b_bk = Vector3::new(-(self.board_size / 2.0), -(self.board_size / 2.0), 0.0);
-
-        let tile_size = self.tile_size();
-
-        let bk_tk = Vector3::new(x as f32 * tile_size, y as f32 * tile_size, 0.0);
-
-        let tk_tc = Vector3::new(tile_size / 2.0, tile_size / 2.0, 0.0);
-
-        let b_t = b_bk + bk_tk + tk_tc;
-
-        let mut transform = Transform::default();
-        transform.set_translation(b_t);
-        transform.set_translation_z(-10.0);
+        let transform = self.tile_transform(index);
 
         let sprite = SpriteRender {
             sprite_sheet,
-            sprite_number: tile_id as usize,
+            sprite_number: if cut { tile_id as usize } else { 0 },
         };
         world
             .create_entity()
@@ -102,57 +171,173 @@ impl Board {
             .build()
     }
 
-    pub fn move_tile_at(&mut self, idx: u32) {
-        let to = self.empty_adjacent(idx).unwrap();
+    /// Adds the number shown over a tile, styled by the world's `NumberStyle`. `TileNumberSystem` keeps it over the
+    /// tile as the tile moves.
+    fn init_number(world: &mut World, tile: Entity, tile_id: TileId) -> Entity {
+        let style = (*world.read_resource::<NumberStyle>()).clone();
+        let font = match &style.font {
+            Some(font) => world.read_resource::<Loader>().load(
+                font.as_str(),
+                TtfFormat,
+                (),
+                &world.read_resource::<AssetStorage<FontAsset>>(),
+            ),
+            None => default_font(world),
+        };
+
+        // Sized and placed by `TileNumberSystem`. Under any other text, such as the menus shown over the board.
+        let transform = UiTransform::new(
+            format!("TileNumber{}", tile_id),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.0,
+            0.0,
+            0.5,
+            0.0,
+            0.0,
+        );
+        world
+            .create_entity()
+            .with(transform)
+            .with(UiText::new(
+                font,
+                tile_id.to_string(),
+                style.text_colour,
+                0.0,
+                LineMode::Single,
+                Anchor::Middle,
+            ))
+            .with(TileNumber { tile })
+            .build()
+    }
+
+    /// Switches the tiles already in the world over to being cut from the given image, or to being numbered without
+    /// one.
+    pub fn set_faces(&self, world: &mut World, image: Option<&str>) {
+        let sprite_sheet = self.load_faces(world, image);
+
+        let numbers: Vec<Entity> = (&world.entities(), &world.read_storage::<TileNumber>())
+            .join()
+            .map(|(entity, _)| entity)
+            .collect();
+        world.delete_entities(&numbers).expect("The numbers should be alive");
+
+        let tiles: Vec<(Entity, TileId)> = (&world.entities(), &world.read_storage::<Tile>())
+            .join()
+            .map(|(entity, tile)| (entity, tile.index))
+            .collect();
+        for (entity, tile_id) in tiles {
+            let sprite = SpriteRender {
+                sprite_sheet: sprite_sheet.clone(),
+                sprite_number: if image.is_some() { tile_id as usize } else { 0 },
+            };
+            world
+                .write_storage::<SpriteRender>()
+                .insert(entity, sprite)
+                .expect("Tile entities should be alive");
+            if image.is_none() {
+                Board::init_number(world, entity, tile_id);
+            }
+        }
+    }
+
+    /// Where a tile in the given slot goes, at the board's current size. Sprites are a single unit across, so the
+    /// transform's scale sets the tile's size.
+    pub fn tile_transform(&self, index: u32) -> Transform {
+        let (x, y) = self.puzzle.idx_xy(index);
+
+        let b_bk = -self.extent() / 2.0;
+
+        let tile_size = self.tile_size();
+
+        let bk_tk = Vector3::new(x as f32 * tile_size, y as f32 * tile_size, 0.0);
 
-        let tiles = &mut self.tiles;
+        let tk_tc = Vector3::new(tile_size / 2.0, tile_size / 2.0, 0.0);
+
+        let b_t = b_bk + bk_tk + tk_tc;
 
-        tiles.swap(to as usize, idx as usize)
+        let mut transform = Transform::default();
+        transform.set_translation(b_t);
+        transform.set_translation_z(-10.0);
+        transform.set_scale(Vector3::new(tile_size, tile_size, 1.0));
+        transform
     }
 
-    fn scramble(&mut self) {
-        // TODO Do something to scramble the board.
-        self.move_tile_at(1)
+    /// Sizes the board to fill as much of a screen of the given size as it can, leaving a margin around it.
+    pub fn fit(&mut self, screen_width: f32, screen_height: f32) {
+        let margin = screen_width.min(screen_height) * MARGIN;
+        let tile_size = ((screen_width - 2.0 * margin) / self.puzzle.tiles_width() as f32)
+            .min((screen_height - 2.0 * margin) / self.puzzle.tiles_height() as f32)
+            .max(0.0);
+
+        self.board_size = tile_size * self.puzzle.tiles_width().max(self.puzzle.tiles_height()) as f32;
     }
 
-    pub fn tile_at(&self, slot: u32) -> Option<TileId> {
-        self.tiles.get(slot as usize).and_then(|it| *it)
+    /// Adds a tile to the empty slot of a solved board, so the whole image shows. Numbered boards are left as they
+    /// are, there's no number for the empty slot.
+    pub fn complete_image(&self, world: &mut World) -> Option<Entity> {
+        if (&world.read_storage::<TileNumber>()).join().next().is_some() {
+            return None;
+        }
+
+        let (sprite_sheet, parent) = {
+            let tiles = world.read_storage::<Tile>();
+            let sprites = world.read_storage::<SpriteRender>();
+            let parents = world.read_storage::<Parent>();
+
+            let (_, sprite, parent) = (&tiles, &sprites, &parents)
+                .join()
+                .next()
+                .expect("The board should have tiles");
+            (sprite.sprite_sheet.clone(), parent.entity)
+        };
+
+        // When solved the empty slot is slot 0, which shows the first piece of the image.
+        Some(self.init_tile(world, sprite_sheet, true, 0, 0, parent))
     }
 
-    pub fn is_empty(&self, idx: u32) -> bool {
-        self.tiles
-            .get(idx as usize)
-            .map_or(false, |it| it.is_none())
+    /// The sprite sheet the tiles are cut from the given image with, or the plain tile numbered ones share without one.
+    fn load_faces(&self, world: &mut World, image: Option<&str>) -> Handle<SpriteSheet> {
+        match image {
+            Some(image) => self.load_sprite_sheet(world, image),
+            None => Board::load_numbered_sheet(world),
+        }
     }
 
-    pub fn empty_adjacent(&self, idx: u32) -> Option<u32> {
-        let a = self.idx_xy(idx);
-        let b = self.adj_xy(a);
+    /// A sprite sheet with a single sprite, a square of the `NumberStyle`'s tile colour a little smaller than a slot.
+    fn load_numbered_sheet(world: &mut World) -> Handle<SpriteSheet> {
+        let loader = world.read_resource::<Loader>();
+
+        let [red, green, blue, alpha] = world.read_resource::<NumberStyle>().tile_colour;
+        let texture = loader.load_from_data(
+            load_from_srgba(Srgba::new(red, green, blue, alpha)).into(),
+            (),
+            &world.read_resource::<AssetStorage<Texture>>(),
+        );
+
+        let sprite_size = (NUMBERED_TILE_FILL, NUMBERED_TILE_FILL);
+        let sprite = Sprite::from((sprite_size, [0.0; 2], [0.0, 1.0, 1.0, 0.0]));
 
-        b.iter()
-            .map(|xy| self.xy_idx(*xy))
-            .filter(|idx| self.is_empty(*idx))
-            .next()
+        loader.load_from_data(
+            SpriteSheet { texture, sprites: vec![sprite] },
+            (),
+            &world.read_resource::<AssetStorage<SpriteSheet>>(),
+        )
     }
 
-    pub fn is_solved(&self) -> bool {
-        let mut it = self.tiles.iter();
-        if it
-            .next()
-            .expect("The first element of tiles should exist")
-            .is_some()
-        {
-            false
+    /// The part of an image of the given size the board is cut from, as the left and top edges and the width and
+    /// height, all as fractions of the image. An image that isn't the board's shape is cropped to it, evenly from
+    /// either side.
+    fn crop(&self, (image_width, image_height): (u32, u32)) -> (f32, f32, f32, f32) {
+        let image_aspect = image_width as f32 / image_height as f32;
+        let board_aspect = self.puzzle.tiles_width() as f32 / self.puzzle.tiles_height() as f32;
+
+        if image_aspect > board_aspect {
+            let width = board_aspect / image_aspect;
+            ((1.0 - width) / 2.0, 0.0, width, 1.0)
         } else {
-            let mut expected = 1;
-            it.all(|index| {
-                if *index == Some(expected) {
-                    expected += 1;
-                    true
-                } else {
-                    false
-                }
-            })
+            let height = image_aspect / board_aspect;
+            (0.0, (1.0 - height) / 2.0, 1.0, height)
         }
     }
 
@@ -166,24 +351,40 @@ impl Board {
             &world.read_resource::<AssetStorage<Texture>>(),
         );
 
+        // The texture's loaded in the background, so its size comes from the file. If that can't be read the image
+        // is stretched over the board instead.
+        let size = match image_path(png_path) {
+            Some(path) => image_size::read(&path).map_err(|err| format!("couldn't read {}: {}", path.display(), err)),
+            None => Err("the application root couldn't be found".to_string()),
+        };
+        let (crop_left, crop_top, crop_width, crop_height) = match size {
+            Ok(size) => self.crop(size),
+            Err(err) => {
+                log::warn!("Stretching {} over the board, {}", png_path, err);
+                (0.0, 0.0, 1.0, 1.0)
+            }
+        };
+
         // Texture coordinates are from the top left, but the board tiles are from the bottom left.
         // Invert Y.
 
-        let img_per_tile = 1.0 / self.tiles_dim as f32;
+        let img_per_tile_x = crop_width / self.puzzle.tiles_width() as f32;
+        let img_per_tile_y = crop_height / self.puzzle.tiles_height() as f32;
 
-        let sprite_size = (self.tile_size(), self.tile_size());
+        // Unit sized, so that tiles can be resized through their transforms.
+        let sprite_size = (1.0, 1.0);
         let offsets = [0.0; 2];
 
-        let sprite_count = self.tiles_dim * self.tiles_dim;
+        let sprite_count = self.puzzle.slots();
         let mut sprites = Vec::with_capacity(sprite_count as usize);
         for i in 0..sprite_count {
-            let (x, y) = self.idx_xy(i);
-            let y = self.tiles_dim - (y + 1);
+            let (x, y) = self.puzzle.idx_xy(i);
+            let y = self.puzzle.tiles_height() - (y + 1);
 
-            let left = img_per_tile * x as f32;
-            let right = img_per_tile * (x + 1) as f32;
-            let top = img_per_tile * y as f32;
-            let bottom = img_per_tile * (y + 1) as f32;
+            let left = crop_left + img_per_tile_x * x as f32;
+            let right = crop_left + img_per_tile_x * (x + 1) as f32;
+            let top = crop_top + img_per_tile_y * y as f32;
+            let bottom = crop_top + img_per_tile_y * (y + 1) as f32;
 
             let sprite = Sprite::from((sprite_size, offsets, [left, right, bottom, top]));
 
@@ -206,17 +407,17 @@ impl Board {
         // C = Cursor
         // W / B = Board Center
 
-        self.check_idx(idx).map(|idx| {
-            let (x, y) = self.idx_xy(idx);
-            let x = x as f32 * self.board_size / self.tiles_dim as f32;
-            let y = y as f32 * self.board_size / self.tiles_dim as f32;
+        self.puzzle.check_idx(idx).map(|idx| {
+            let (x, y) = self.puzzle.idx_xy(idx);
+            let x = x as f32 * self.tile_size();
+            let y = y as f32 * self.tile_size();
 
             let kxtk = Vector3::new(x, y, 0.0);
 
-            let tkxt = Vector3::new(self.board_size / self.tiles_dim as f32 / 2.0, self.board_size / self.tiles_dim as f32 / 2.0, 0.0);
+            let tkxt = Vector3::new(self.tile_size() / 2.0, self.tile_size() / 2.0, 0.0);
 
             // Transform from the bottom left corner (where tile 0 0's corner is) to the center.
-            let kxb: Vector3<f32> = Vector3::new(self.board_size / 2.0, self.board_size / 2.0, 0.0);
+            let kxb: Vector3<f32> = self.extent() / 2.0;
 
             let xyz: Vector3<f32> = -kxb + kxtk + tkxt;
             let x = xyz.x;
@@ -234,7 +435,7 @@ impl Board {
         // W / B = Board Center
 
         // Transform from the bottom left corner (where tile 0 0's corner is) to the center.
-        let kxb: Vector3<f32> = Vector3::new(self.board_size / 2.0, self.board_size / 2.0, 0.0);
+        let kxb: Vector3<f32> = self.extent() / 2.0;
 
         // Transform from the center of the board to the cursor (assuming actual screen coords have been into'd world coords).
         let bxc: Vector3<f32> = Vector3::new(x, y, 0.0);
@@ -243,155 +444,34 @@ impl Board {
         let kxc: Vector3<f32> = kxb + bxc;
 
         // Scale the board coordinates to tile coordinates and floor the float values to specific tile coordinates.
-        let x: f32 = kxc.x / self.board_size * self.tiles_dim as f32;
-        let y: f32 = kxc.y / self.board_size * self.tiles_dim as f32;
+        let x: f32 = kxc.x / self.tile_size();
+        let y: f32 = kxc.y / self.tile_size();
 
         let x = x.floor() as i32;
         let y = y.floor() as i32;
 
         // Check that the tile coordinates are actually valid, then transform it to a slot index.
-        self.check_xy((x, y)).map(|xy| self.xy_idx(xy))
-    }
-
-    fn idx_max(&self) -> u32 {
-        self.tiles_dim * self.tiles_dim
+        self.puzzle.check_xy((x, y)).map(|xy| self.puzzle.xy_idx(xy))
     }
 
     fn tile_size(&self) -> f32 {
-        self.board_size / self.tiles_dim as f32
-    }
-
-    fn adj_xy(&self, (x, y): (u32, u32)) -> Vec<(u32, u32)> {
-        let x = x as i32;
-        let y = y as i32;
-
-        let it = vec![(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)];
-
-        let mut res = Vec::with_capacity(4);
-        for xy in it {
-            self.check_xy(xy).map(|xy| res.push(xy));
-        }
-
-        res
+        self.board_size / self.puzzle.tiles_width().max(self.puzzle.tiles_height()) as f32
     }
 
-    fn check_x(&self, x: i32) -> Option<u32> {
-        if (0i32..(self.tiles_dim as i32)).contains(&x) {
-            Some(x as u32)
-        } else {
-            None
-        }
-    }
-
-    fn check_y(&self, x: i32) -> Option<u32> {
-        if (0i32..(self.tiles_dim as i32)).contains(&x) {
-            Some(x as u32)
-        } else {
-            None
-        }
-    }
-
-    fn check_xy(&self, (x, y): (i32, i32)) -> Option<(u32, u32)> {
-        self.check_x(x)
-            .and_then(|x| self.check_y(y).map(|y| (x, y)))
-    }
-
-    fn check_idx(&self, idx: i32) -> Option<u32> {
-        if (0i32..(self.idx_max() as i32)).contains(&idx) {
-            Some(idx as u32)
-        } else {
-            None
-        }
-    }
-
-    fn idx_xy(&self, idx: u32) -> (u32, u32) {
-        let y = idx / self.tiles_dim;
-        let x = idx % self.tiles_dim;
-
-        (x, y)
-    }
-
-    fn xy_idx(&self, (x, y): (u32, u32)) -> u32 {
-        x + y * self.tiles_dim
+    /// The width and height of the whole board in world units.
+    fn extent(&self) -> Vector3<f32> {
+        Vector3::new(
+            self.puzzle.tiles_width() as f32 * self.tile_size(),
+            self.puzzle.tiles_height() as f32 * self.tile_size(),
+            0.0,
+        )
     }
 }
 
-#[test]
-fn checks() {
-    let board = Board {
-        tiles_dim: 2,
-        tiles: vec![None, Some(2), Some(1), Some(3)],
-        board_size: 600.0,
-    };
-
-    assert_eq!(board.check_x(1), Some(1));
-    assert_eq!(board.check_y(0), Some(0));
-    assert_eq!(board.check_idx(3), Some(3));
-    assert_eq!(board.check_xy((1, 1)), Some((1, 1)));
-
-    assert_eq!(board.check_x(3), None);
-    assert_eq!(board.check_y(4), None);
-    assert_eq!(board.check_idx(8), None);
-    assert_eq!(board.check_xy((1, 2)), None);
-
-    assert_eq!(board.check_x(-1), None);
-    assert_eq!(board.check_y(-2), None);
-    assert_eq!(board.check_idx(-2), None);
-    assert_eq!(board.check_xy((-1, -2)), None);
-    assert_eq!(board.check_xy((-1, -2)), None);
-}
-
-#[test]
-fn idx_xy() {
-    let board = Board {
-        tiles_dim: 2,
-        tiles: vec![None, Some(2), Some(1), Some(3)],
-        board_size: 600.0,
-    };
-
-    let (x, y) = board.idx_xy(0);
-    assert_eq!(x, 0);
-    assert_eq!(y, 0);
-
-    let (x, y) = board.idx_xy(1);
-    assert_eq!(x, 1);
-    assert_eq!(y, 0);
-
-    let (x, y) = board.idx_xy(2);
-    assert_eq!(x, 0);
-    assert_eq!(y, 1);
-
-    let (x, y) = board.idx_xy(3);
-    assert_eq!(x, 1);
-    assert_eq!(y, 1);
-}
-
-#[test]
-fn xy_idx() {
-    let board = Board {
-        tiles_dim: 2,
-        tiles: vec![None, Some(2), Some(1), Some(3)],
-        board_size: 600.0,
-    };
-
-    let idx = board.xy_idx((0, 0));
-    assert_eq!(idx, 0);
-
-    let idx = board.xy_idx((1, 0));
-    assert_eq!(idx, 1);
-
-    let idx = board.xy_idx((0, 1));
-    assert_eq!(idx, 2);
-    let idx = board.xy_idx((1, 1));
-
-    assert_eq!(idx, 3);
-}
-
 #[test]
 fn world_idx() {
     let board = Board {
-        tiles_dim: 2,
-        tiles: vec![None, Some(2), Some(1), Some(3)],
+        puzzle: Puzzle::with_tiles(2, 2, vec![None, Some(2), Some(1), Some(3)], 0),
         board_size: 600.0,
     };
 
@@ -400,44 +480,43 @@ fn world_idx() {
     assert_eq!(board.world_coord_idx(-200.0, 200.0), Some(2));
     assert_eq!(board.world_coord_idx(200.0, 200.0), Some(3));
     assert_eq!(board.world_coord_idx(400.0, -200.0), None);
+
+    // Three 200 unit tiles across and two up, centred on the origin.
+    let board = Board { puzzle: Puzzle::solved(3, 2, 0), board_size: 600.0 };
+    assert_eq!(board.world_coord_idx(-250.0, -150.0), Some(0));
+    assert_eq!(board.world_coord_idx(250.0, -150.0), Some(2));
+    assert_eq!(board.world_coord_idx(0.0, 150.0), Some(4));
+    assert_eq!(board.world_coord_idx(0.0, 250.0), None);
+    assert_eq!(board.idx_world(5), Some(Point3::new(200.0, 100.0, 0.0)));
 }
 
 #[test]
-fn adj() {
-    let board = Board {
-        tiles_dim: 2,
-        tiles: vec![None, Some(2), Some(1), Some(3)],
-        board_size: 600.0,
-    };
-
-    let adj = board.adj_xy((0, 0));
-    assert_eq!(adj.contains(&(1, 0)), true);
-    assert_eq!(adj.contains(&(0, 1)), true);
-    assert_eq!(adj.len(), 2);
-
-    let board = Board {
-        tiles_dim: 3,
-        tiles: vec![
-            None, Some(2), Some(1),
-            Some(2), Some(2), Some(1),
-            Some(2), Some(2), Some(1),
-        ],
-        board_size: 600.0,
-    };
-
-    let adj = board.adj_xy((1, 1));
-    assert_eq!(adj.contains(&(1, 0)), true);
-    assert_eq!(adj.contains(&(0, 1)), true);
-    assert_eq!(adj.contains(&(1, 2)), true);
-    assert_eq!(adj.contains(&(2, 1)), true);
-    assert_eq!(adj.len(), 4);
+fn fit() {
+    // 30 units of margin all round leaves 740x540 for the board, so the height decides the tile size.
+    let mut board = Board::new(Puzzle::solved(4, 4, 0));
+    board.fit(800.0, 600.0);
+    assert_eq!(board.tile_size(), 135.0);
+    assert_eq!(board.world_coord_idx(-269.0, -269.0), Some(0));
+    assert_eq!(board.world_coord_idx(-271.0, -269.0), None);
+
+    // A wide board runs out of width first.
+    let mut board = Board::new(Puzzle::solved(8, 4, 0));
+    board.fit(800.0, 600.0);
+    assert_eq!(board.tile_size(), 92.5);
+    assert_eq!(board.world_coord_idx(369.0, 184.0), Some(31));
+    assert_eq!(board.world_coord_idx(371.0, 184.0), None);
+
+    // Resizing moves the tiles along with the board.
+    board.fit(400.0, 300.0);
+    assert_eq!(board.tile_size(), 46.25);
+    assert_eq!(board.tile_transform(31).translation().x, 185.0 - 46.25 / 2.0);
+    assert_eq!(board.tile_transform(31).scale().x, 46.25);
 }
 
 #[test]
 fn idx_world() {
     let board = Board {
-        tiles_dim: 2,
-        tiles: vec![None, Some(2), Some(1), Some(3)],
+        puzzle: Puzzle::with_tiles(2, 2, vec![None, Some(2), Some(1), Some(3)], 0),
         board_size: 600.0,
     };
 
@@ -447,12 +526,20 @@ fn idx_world() {
 }
 
 #[test]
-fn board_solved() {
-    let board = Board {
-        tiles_dim: 2,
-        tiles: vec![None, Some(1), Some(2), Some(3)],
-        board_size: 600.0,
-    };
-
-    assert_eq!(board.is_solved(), true);
+fn crop_to_shape() {
+    // A landscape image on a square board loses a quarter of its width from either side.
+    let board = Board::new(Puzzle::solved(4, 4, 0));
+    assert_eq!(board.crop((800, 400)), (0.25, 0.0, 0.5, 1.0));
+    assert_eq!(board.crop((300, 300)), (0.0, 0.0, 1.0, 1.0));
+
+    // A square image on a wide board loses from the top and bottom.
+    let board = Board::new(Puzzle::solved(4, 2, 0));
+    assert_eq!(board.crop((600, 600)), (0.0, 0.25, 1.0, 0.5));
+
+    assert_eq!(shape_for_image((1920, 1080), 4, 2), (4, 2));
+    assert_eq!(shape_for_image((1920, 1080), 6, 2), (6, 3));
+    assert_eq!(shape_for_image((600, 800), 5, 2), (4, 5));
+    assert_eq!(shape_for_image((600, 600), 4, 2), (4, 4));
+    assert_eq!(shape_for_image((4000, 100), 4, 2), (4, 2));
+    assert_eq!(shape_for_image((u32::MAX, u32::MAX / 2), 10, 2), (10, 5));
 }
@@ -0,0 +1,133 @@
+use std::path::PathBuf;
+
+use fresh::options;
+use fresh::puzzle::Difficulty;
+
+use super::states::GameOptions;
+
+pub const USAGE: &str = "\
+Usage:
+    fresh [options]          play, from the main menu unless any options are given
+
+Options:
+    --width <tiles>          how many tiles wide the board is
+    --height <tiles>         how many tiles high the board is
+    --image <file>           the image to cut the board from, within the assets directory or by absolute path
+    --seed <number>          the seed to scramble the board with
+    --difficulty <name>      how hard to scramble the board: easy, medium or random
+    --load <file>            continue a saved game
+    --puzzle <file>          play a puzzle: a row of tile numbers per line from the top, 0 for the empty slot
+    --help                   show this message";
+
+/// What the game was asked to do on the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Play(Setup),
+    Help,
+}
+
+/// How to set up the first game, as given on the command line. Anything not given is left to the main menu.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Setup {
+    pub tiles_width: Option<u32>,
+    pub tiles_height: Option<u32>,
+    pub image: Option<String>,
+    pub seed: Option<u64>,
+    /// `Some(None)` asks for a uniformly random board.
+    pub difficulty: Option<Option<Difficulty>>,
+    /// A saved game to continue.
+    pub load: Option<PathBuf>,
+    /// A puzzle file to play.
+    pub puzzle: Option<PathBuf>,
+}
+
+impl Setup {
+    /// Whether nothing was given, so the game should start from the main menu.
+    pub fn is_empty(&self) -> bool {
+        *self == Setup::default()
+    }
+
+    /// Overrides the options with whatever was given.
+    pub fn apply(&self, options: &mut GameOptions) {
+        options.tiles_width = self.tiles_width.unwrap_or(options.tiles_width);
+        options.tiles_height = self.tiles_height.unwrap_or(options.tiles_height);
+        if let Some(image) = &self.image {
+            options.image = image.clone();
+        }
+        options.difficulty = self.difficulty.unwrap_or(options.difficulty);
+        options.seed = self.seed.or(options.seed);
+    }
+}
+
+/// Parses the command line arguments, without the program name. The error says what's wrong with them.
+pub fn parse(args: &[String]) -> Result<Command, String> {
+    let parsed = match options::parse(args, USAGE)? {
+        Some(parsed) => parsed,
+        None => return Ok(Command::Help),
+    };
+    if let Some(arg) = parsed.rest.first() {
+        return Err(format!("Unknown option {}\n\n{}", arg, USAGE));
+    }
+
+    let mut setup = Setup::default();
+    for (flag, value) in parsed.flags {
+        match flag.as_str() {
+            "--width" => setup.tiles_width = Some(options::tiles(&flag, &value)?),
+            "--height" => setup.tiles_height = Some(options::tiles(&flag, &value)?),
+            "--image" => setup.image = Some(value),
+            "--seed" => setup.seed = Some(options::seed(&value)?),
+            "--difficulty" => setup.difficulty = Some(options::difficulty(&value)?),
+            "--load" => setup.load = Some(value.into()),
+            "--puzzle" => setup.puzzle = Some(value.into()),
+            _ => return Err(format!("Unknown option {}\n\n{}", flag, USAGE)),
+        }
+    }
+
+    // A saved game or puzzle brings its own board.
+    let board_given = setup.tiles_width.is_some()
+        || setup.tiles_height.is_some()
+        || setup.seed.is_some()
+        || setup.difficulty.is_some();
+    match (&setup.load, &setup.puzzle) {
+        (Some(_), Some(_)) => return Err("--load and --puzzle can't both be given".to_string()),
+        (Some(_), None) | (None, Some(_)) if board_given => {
+            return Err("--width, --height, --seed and --difficulty don't go with --load or --puzzle".to_string())
+        }
+        _ => {}
+    }
+
+    Ok(Command::Play(setup))
+}
+
+#[test]
+fn parse_args() {
+    let parse = |args: &str| parse(&args.split_whitespace().map(str::to_string).collect::<Vec<_>>());
+
+    assert_eq!(parse(""), Ok(Command::Play(Setup::default())));
+    assert_eq!(parse("--help"), Ok(Command::Help));
+
+    assert_eq!(
+        parse("--width 6 --height=4 --image photo.png --seed 42 --difficulty Medium"),
+        Ok(Command::Play(Setup {
+            tiles_width: Some(6),
+            tiles_height: Some(4),
+            image: Some("photo.png".to_string()),
+            seed: Some(42),
+            difficulty: Some(Some(Difficulty::MEDIUM)),
+            ..Setup::default()
+        }))
+    );
+    assert_eq!(
+        parse("--difficulty random"),
+        Ok(Command::Play(Setup { difficulty: Some(None), ..Setup::default() }))
+    );
+    assert_eq!(
+        parse("--load saves/game.ron"),
+        Ok(Command::Play(Setup { load: Some("saves/game.ron".into()), ..Setup::default() }))
+    );
+
+    assert!(parse("--colour blue").is_err());
+    assert!(parse("moves.txt").is_err());
+    assert!(parse("--load a.ron --puzzle b.txt").is_err());
+    assert!(parse("--puzzle b.txt --width 5").is_err());
+}
@@ -4,3 +4,10 @@ use amethyst::ecs::*;
 pub struct Tile {
     pub index: u32,
 }
+
+/// The number shown over a tile when the tiles are numbered rather than cut from an image.
+#[derive(Component)]
+pub struct TileNumber {
+    /// The tile's entity.
+    pub tile: Entity,
+}
@@ -0,0 +1,263 @@
+use super::puzzle::*;
+use super::solver::SolveError;
+use std::collections::VecDeque;
+
+/// Finds a sequence of moves that solves the board, without trying to make it short.
+///
+/// Unlike `solver::solve` this works on boards of any size or shape in polynomial time. Tiles are put home one at a
+/// time, locking each in place: the rows from the top down until only the bottom two are left, then those two rows a
+/// column at a time from the right, and finally the 2x2 block around the empty slot's home. The moves are slots to hand
+/// to `Puzzle::move_tile_at`, as with `solver::solve`.
+pub fn solve(board: &Puzzle) -> Result<Vec<u32>, SolveError> {
+    if !board.is_solvable() {
+        return Err(SolveError::Unsolvable);
+    }
+
+    let mut builder = Builder::new(board);
+    let (width, height) = (builder.width, builder.height);
+    if width < 2 || height < 2 {
+        // The tiles in a single row or column can't be reordered, only the empty slot can be moved home.
+        while builder.empty > 0 {
+            builder.slide(builder.empty - 1);
+        }
+        return if builder.is_solved() { Ok(builder.moves) } else { Err(SolveError::Unsolvable) };
+    }
+
+    for y in (2..height).rev() {
+        for x in 0..width - 2 {
+            builder.place(x, y);
+        }
+
+        let area: Vec<_> = (y - 2..=y)
+            .flat_map(|y| (width - 2..width).map(move |x| (x, y)))
+            .collect();
+        builder.place_pair((width - 1, y), (width - 2, y), &area);
+    }
+
+    for x in (2..width).rev() {
+        let area: Vec<_> = (0..2).flat_map(|y| (x - 2..=x).map(move |x| (x, y))).collect();
+        builder.place_pair((x, 0), (x, 1), &area);
+    }
+
+    // Whatever is left in the bottom left 2x2 block is a rotation of the solved block, so going round it is enough.
+    let cycle = [(0, 0), (0, 1), (1, 1), (1, 0)];
+    while !builder.is_solved() {
+        let empty = builder.xy(builder.empty);
+        let (x, y) = cycle
+            .iter()
+            .position(|it| *it == empty)
+            .map(|idx| cycle[(idx + 1) % cycle.len()])
+            .expect("The empty slot should be in the last block");
+        builder.slide(builder.idx(x, y));
+    }
+
+    Ok(builder.moves)
+}
+
+/// Works a board towards solved, recording the moves made along the way.
+struct Builder {
+    width: usize,
+    height: usize,
+    tiles: Vec<u32>,
+    empty: usize,
+    /// Slots holding tiles that are home and mustn't be disturbed.
+    locked: Vec<bool>,
+    moves: Vec<u32>,
+}
+
+impl Builder {
+    fn new(board: &Puzzle) -> Self {
+        let tiles: Vec<u32> = board.tiles().iter().map(|it| it.unwrap_or(0)).collect();
+        let empty = tiles.iter().position(|it| *it == 0).expect("A board should have an empty slot");
+
+        Builder {
+            width: board.tiles_width() as usize,
+            height: board.tiles_height() as usize,
+            locked: vec![false; tiles.len()],
+            tiles,
+            empty,
+            moves: vec![],
+        }
+    }
+
+    fn idx(&self, x: usize, y: usize) -> usize {
+        x + y * self.width
+    }
+
+    fn xy(&self, idx: usize) -> (usize, usize) {
+        (idx % self.width, idx / self.width)
+    }
+
+    fn is_solved(&self) -> bool {
+        self.tiles.iter().enumerate().all(|(slot, tile)| *tile as usize == slot)
+    }
+
+    fn neighbours(&self, idx: usize) -> Vec<usize> {
+        let (x, y) = self.xy(idx);
+        let mut res = Vec::with_capacity(4);
+        if x > 0 {
+            res.push(idx - 1);
+        }
+        if x + 1 < self.width {
+            res.push(idx + 1);
+        }
+        if y > 0 {
+            res.push(idx - self.width);
+        }
+        if y + 1 < self.height {
+            res.push(idx + self.width);
+        }
+        res
+    }
+
+    /// Slides the tile in the given slot, which must be next to the empty slot, into the empty slot.
+    fn slide(&mut self, idx: usize) {
+        debug_assert!(self.neighbours(self.empty).contains(&idx));
+
+        self.tiles.swap(idx, self.empty);
+        self.empty = idx;
+        self.moves.push(idx as u32);
+    }
+
+    /// Moves the tile that belongs in slot `tile` by the shortest route around the locked slots until `goal` accepts
+    /// where it and the empty slot are, returning where the tile ended up.
+    ///
+    /// Searches over where the tile and the empty slot both are, so it finds a way whenever there is one, however
+    /// boxed in the tile is.
+    fn bring(&mut self, tile: usize, goal: impl Fn(usize, usize) -> bool) -> usize {
+        let cells = self.tiles.len();
+        let start = self.tiles.iter().position(|it| *it as usize == tile).expect("Every tile should be on the board");
+
+        // States are a tile slot and an empty slot, packed into one index.
+        let mut previous = vec![None; cells * cells];
+        let mut queue = VecDeque::new();
+        previous[start * cells + self.empty] = Some(start * cells + self.empty);
+        queue.push_back(start * cells + self.empty);
+
+        let mut found = None;
+        while let Some(state) = queue.pop_front() {
+            let (tile_idx, empty) = (state / cells, state % cells);
+            if goal(tile_idx, empty) {
+                found = Some(state);
+                break;
+            }
+
+            for next in self.neighbours(empty) {
+                if self.locked[next] {
+                    continue;
+                }
+
+                // The empty slot swapping with the tile moves the tile into where the empty slot was.
+                let next_tile = if next == tile_idx { empty } else { tile_idx };
+                let next_state = next_tile * cells + next;
+                if previous[next_state].is_none() {
+                    previous[next_state] = Some(state);
+                    queue.push_back(next_state);
+                }
+            }
+        }
+
+        let found = found.expect("The tile should be able to reach its target");
+        let mut route = vec![];
+        let mut state = found;
+        while state != start * cells + self.empty {
+            route.push(state % cells);
+            state = previous[state].expect("Every visited state should have a predecessor");
+        }
+        for idx in route.into_iter().rev() {
+            self.slide(idx);
+        }
+
+        found / cells
+    }
+
+    /// Puts the tile that belongs in slot (`x`, `y`) home and locks it there.
+    fn place(&mut self, x: usize, y: usize) {
+        let home = self.idx(x, y);
+        self.bring(home, |idx, _| idx == home);
+        self.locked[home] = true;
+    }
+
+    /// Puts the tiles that belong in the last two slots of a row or column home together, and locks them there. `first`
+    /// is the slot in the corner of the unsolved part of the board.
+    ///
+    /// Putting them home one after the other can box the second in behind the first. Instead, the first tile goes
+    /// home, the second tile and the empty slot are brought into `area`, a 2x3 block of free slots around their homes,
+    /// and a search over just where those three are within the block finds a way to put both tiles home at once.
+    fn place_pair(&mut self, first: (usize, usize), second: (usize, usize), area: &[(usize, usize)]) {
+        let area: Vec<usize> = area.iter().map(|(x, y)| self.idx(*x, *y)).collect();
+        let homes = (self.idx(first.0, first.1), self.idx(second.0, second.1));
+        if self.tiles[homes.0] as usize == homes.0 && self.tiles[homes.1] as usize == homes.1 {
+            self.locked[homes.0] = true;
+            self.locked[homes.1] = true;
+            return;
+        }
+
+        let first_idx = self.bring(homes.0, |idx, _| idx == homes.0);
+        self.locked[first_idx] = true;
+        let second_idx = self.bring(homes.1, |idx, empty| area.contains(&idx) && area.contains(&empty));
+        self.locked[first_idx] = false;
+
+        // States are the positions within the area of the first tile, the second tile and the empty slot.
+        let len = area.len();
+        let local = |idx: usize| area.iter().position(|it| *it == idx).expect("Should be within the area");
+        let start = (local(first_idx) * len + local(second_idx)) * len + local(self.empty);
+        let goal = (local(homes.0) * len + local(homes.1)) * len;
+
+        let mut previous = vec![None; len * len * len];
+        let mut queue = VecDeque::new();
+        previous[start] = Some(start);
+        queue.push_back(start);
+
+        while let Some(state) = queue.pop_front() {
+            let (first_idx, second_idx, empty) = (state / len / len, state / len % len, state % len);
+            if (first_idx, second_idx) == (goal / len / len, goal / len % len) {
+                let mut route = vec![];
+                let mut state = state;
+                while state != start {
+                    route.push(area[state % len]);
+                    state = previous[state].expect("Every visited state should have a predecessor");
+                }
+                for idx in route.into_iter().rev() {
+                    self.slide(idx);
+                }
+
+                self.locked[homes.0] = true;
+                self.locked[homes.1] = true;
+                return;
+            }
+
+            for next in self.neighbours(area[empty]).into_iter().filter(|it| area.contains(it)) {
+                let next = local(next);
+                let moved = |idx: usize| if idx == next { empty } else { idx };
+                let next_state = (moved(first_idx) * len + moved(second_idx)) * len + next;
+                if previous[next_state].is_none() {
+                    previous[next_state] = Some(state);
+                    queue.push_back(next_state);
+                }
+            }
+        }
+
+        panic!("Both tiles should be able to get home within the area");
+    }
+}
+
+#[test]
+fn solves() {
+    for tiles_width in 2..11 {
+        for tiles_height in 2..11 {
+            for seed in 0..3 {
+                let mut board = Puzzle::solved(tiles_width, tiles_height, seed);
+                board.scramble();
+
+                for slot in solve(&board).unwrap() {
+                    board.move_tile_at(slot);
+                }
+                assert!(board.is_solved(), "{:?}", board);
+            }
+        }
+    }
+
+    assert_eq!(solve(&Puzzle::solved(5, 5, 0)), Ok(vec![]));
+}
+
@@ -0,0 +1,175 @@
+use super::puzzle::{Puzzle, TileId};
+use serde::{Deserialize, Serialize};
+
+/// A single tile sliding from one slot into the empty slot next to it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Slide {
+    /// The slot the tile slid from.
+    pub from: u32,
+    /// The slot the tile slid to.
+    pub to: u32,
+    /// The id of the tile that slid.
+    pub tile: TileId,
+}
+
+impl Slide {
+    /// The slide that puts the tile back where it came from.
+    pub fn reversed(&self) -> Slide {
+        Slide {
+            from: self.to,
+            to: self.from,
+            tile: self.tile,
+        }
+    }
+
+    /// The slides moving the line of tiles from the given slot up to the empty slot along by one, if the slot's in line
+    /// with the empty slot, in the order they can be made.
+    pub fn line(puzzle: &Puzzle, from: u32) -> Option<Vec<Slide>> {
+        let slots = puzzle.line_to_empty(from)?;
+
+        // Each tile takes the slot of the one ahead of it, the first one the empty slot.
+        let targets = std::iter::once(puzzle.empty_slot()).chain(slots.iter().copied());
+        slots
+            .iter()
+            .zip(targets)
+            .map(|(from, to)| puzzle.tile_at(*from).map(|tile| Slide { from: *from, to, tile }))
+            .collect()
+    }
+}
+
+/// Every move made on the board, so they can be undone and redone. In the game it lives in the world alongside the
+/// `Board`.
+///
+/// A move is a line of tiles sliding together, given as the slides that make it up in the order they can be made one
+/// at a time: the tile next to the empty slot first. Most moves are a single slide.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    /// Moves made, oldest first.
+    done: Vec<Vec<Slide>>,
+    /// Moves undone, most recently undone last.
+    undone: Vec<Vec<Slide>>,
+}
+
+impl History {
+    /// Records a newly made move. Anything undone can't be redone anymore.
+    pub fn record(&mut self, slides: Vec<Slide>) {
+        self.done.push(slides);
+        self.undone.clear();
+    }
+
+    /// The move that would undo the last move made, if there is one.
+    pub fn next_undo(&self) -> Option<Vec<Slide>> {
+        // The last tile to slide is now next to the empty slot, so it goes back first.
+        self.done
+            .last()
+            .map(|slides| slides.iter().rev().map(Slide::reversed).collect())
+    }
+
+    /// The move that would redo the last move undone, if there is one.
+    pub fn next_redo(&self) -> Option<Vec<Slide>> {
+        self.undone.last().cloned()
+    }
+
+    /// Records that the last move made was undone, by making the move `next_undo` gave.
+    pub fn undo(&mut self) {
+        if let Some(slides) = self.done.pop() {
+            self.undone.push(slides);
+        }
+    }
+
+    /// Every slide in the history, whether it's been undone or not.
+    pub fn slides(&self) -> impl Iterator<Item = &Slide> {
+        self.done.iter().chain(&self.undone).flatten()
+    }
+
+    /// The moves made and not undone, oldest first.
+    pub fn made(&self) -> impl Iterator<Item = &[Slide]> {
+        self.done.iter().map(Vec::as_slice)
+    }
+
+    /// Records that the last move undone was redone, by making the move `next_redo` gave.
+    pub fn redo(&mut self) {
+        if let Some(slides) = self.undone.pop() {
+            self.done.push(slides);
+        }
+    }
+}
+
+#[test]
+fn undo_redo() {
+    use super::puzzle::Puzzle;
+
+    // Slides the line of tiles from the given slot up to the empty slot.
+    fn slide(board: &mut Puzzle, from: u32) -> Vec<Slide> {
+        let slots = board.line_to_empty(from).unwrap();
+        let mut to = board.empty_slot();
+        let mut slides = vec![];
+        for slot in slots {
+            slides.push(Slide {
+                from: slot,
+                to,
+                tile: board.tile_at(slot).unwrap(),
+            });
+            board.move_tile_at(slot);
+            to = slot;
+        }
+        slides
+    }
+
+    let mut board = Puzzle::solved(4, 4, 0);
+    board.scramble();
+    let start = board.tiles().to_vec();
+
+    let mut history = History::default();
+    let mut arrangements = vec![start.clone()];
+    for i in 0..20 {
+        // Alternate between lines along the row and the column, as far away as they go, so the board keeps changing.
+        let from = (0..16)
+            .filter(|idx| board.line_to_empty(*idx).is_some())
+            .nth(i % 2)
+            .unwrap();
+        history.record(slide(&mut board, from));
+        arrangements.push(board.tiles().to_vec());
+    }
+
+    // Undoing walks back through every arrangement the board was in.
+    while let Some(undo) = history.next_undo() {
+        for slide in undo {
+            assert_eq!(board.tile_at(slide.from), Some(slide.tile));
+            board.move_tile_at(slide.from);
+        }
+        history.undo();
+        arrangements.pop();
+        assert_eq!(board.tiles(), &arrangements.last().unwrap()[..]);
+    }
+    assert_eq!(board.tiles(), &start[..]);
+
+    // Redoing walks forwards again, until a new move forgets whatever's left to redo.
+    for _ in 0..5 {
+        let redo = history.next_redo().unwrap();
+        assert_eq!(slide(&mut board, redo.last().unwrap().from), redo);
+        history.redo();
+    }
+    assert!(history.next_redo().is_some());
+    let from = (0..16).find(|idx| board.line_to_empty(*idx).is_some()).unwrap();
+    history.record(slide(&mut board, from));
+    assert_eq!(history.next_redo(), None);
+}
+
+#[test]
+fn line() {
+    // + - - - +
+    // | 6 7 8 |
+    // | 3 4 5 |
+    // | _ 1 2 |
+    // + - - - +
+    let board = Puzzle::solved(3, 3, 0);
+
+    assert_eq!(
+        Slide::line(&board, 2),
+        Some(vec![Slide { from: 1, to: 0, tile: 1 }, Slide { from: 2, to: 1, tile: 2 }])
+    );
+    assert_eq!(Slide::line(&board, 3), Some(vec![Slide { from: 3, to: 0, tile: 3 }]));
+    assert_eq!(Slide::line(&board, 4), None);
+    assert_eq!(Slide::line(&board, 0), None);
+}
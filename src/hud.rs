@@ -0,0 +1,82 @@
+use amethyst::prelude::*;
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::Entity,
+    ui::{get_default_font, Anchor, FontAsset, LineMode, UiText, UiTransform},
+};
+
+use fresh::stats::{format_duration, Stats};
+
+/// The overlay in the corner of the screen showing how the player is getting on. Lives in the world so the
+/// `HudSystem` can keep it up to date.
+pub struct Hud {
+    /// The text entities making up the overlay, a line each, top first.
+    pub lines: Vec<Entity>,
+}
+
+impl Hud {
+    const TEXT_COLOUR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+    const FONT_SIZE: f32 = 20.0;
+    /// How far in from the corner of the screen the overlay sits.
+    const MARGIN: f32 = 10.0;
+
+    /// Adds the overlay's text to the world, showing the given stats to start with.
+    pub fn new(world: &mut World, stats: &Stats) -> Hud {
+        let font = get_default_font(
+            &world.read_resource::<Loader>(),
+            &world.read_resource::<AssetStorage<FontAsset>>(),
+        );
+
+        let lines = Hud::lines(stats)
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let transform = UiTransform::new(
+                    format!("hud_{}", i),
+                    Anchor::TopLeft,
+                    Anchor::TopLeft,
+                    Hud::MARGIN,
+                    -Hud::MARGIN - Hud::FONT_SIZE * 1.25 * i as f32,
+                    1.0,
+                    300.0,
+                    Hud::FONT_SIZE * 1.25,
+                );
+
+                world
+                    .create_entity()
+                    .with(transform)
+                    .with(UiText::new(
+                        font.clone(),
+                        text.clone(),
+                        Hud::TEXT_COLOUR,
+                        Hud::FONT_SIZE,
+                        LineMode::Single,
+                        Anchor::MiddleLeft,
+                    ))
+                    .build()
+            })
+            .collect();
+
+        Hud { lines }
+    }
+
+    /// The text of each line of the overlay for the given stats.
+    pub fn lines(stats: &Stats) -> Vec<String> {
+        vec![
+            format!("Moves: {}", stats.moves),
+            format!("Time: {}", format_duration(stats.elapsed)),
+            format!("Moves/s: {:.2}", stats.moves_per_second()),
+        ]
+    }
+}
+
+#[test]
+fn lines() {
+    let stats = Stats {
+        moves: 15,
+        elapsed: std::time::Duration::from_millis(187_500),
+        ..Stats::default()
+    };
+    assert_eq!(Hud::lines(&stats), vec!["Moves: 15", "Time: 3:07", "Moves/s: 0.08"]);
+    assert_eq!(Hud::lines(&Stats::default()), vec!["Moves: 0", "Time: 0:00", "Moves/s: 0.00"]);
+}
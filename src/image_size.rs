@@ -0,0 +1,103 @@
+//! Reads the width and height of a PNG or JPEG image from its header, so the board can be fitted to the image before
+//! Amethyst has loaded it.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// How much of the start of a file is read looking for the size. JPEGs can have a lot of metadata ahead of it.
+const HEADER_LIMIT: u64 = 1 << 20;
+/// The widest or tallest an image can be, in pixels. Anything bigger is taken to be corrupt.
+const MAX_SIZE: u32 = 1 << 16;
+
+/// The width and height of the image in the file, in pixels.
+pub fn read(path: &Path) -> io::Result<(u32, u32)> {
+    let mut bytes = vec![];
+    File::open(path)?.take(HEADER_LIMIT).read_to_end(&mut bytes)?;
+    parse(&bytes).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a PNG or JPEG image of a usable size"))
+}
+
+/// The width and height of a PNG or JPEG image, or `None` if it's neither, or either side is empty or bigger than
+/// `MAX_SIZE`.
+pub fn parse(bytes: &[u8]) -> Option<(u32, u32)> {
+    parse_header(bytes).filter(|(width, height)| (1..=MAX_SIZE).contains(width) && (1..=MAX_SIZE).contains(height))
+}
+
+fn parse_header(bytes: &[u8]) -> Option<(u32, u32)> {
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+    if bytes.starts_with(PNG_SIGNATURE) {
+        // The IHDR chunk comes first: its length and type, then the width and height.
+        if bytes.get(12..16)? != b"IHDR" {
+            return None;
+        }
+        return Some((be_u32(bytes.get(16..20)?), be_u32(bytes.get(20..24)?)));
+    }
+
+    if bytes.starts_with(&[0xff, 0xd8]) {
+        return parse_jpeg(&bytes[2..]);
+    }
+
+    None
+}
+
+/// Walks the segments after a JPEG's start of image marker to the start of frame, which holds the size.
+fn parse_jpeg(mut bytes: &[u8]) -> Option<(u32, u32)> {
+    loop {
+        // Markers can be padded with any number of 0xff bytes.
+        let start = bytes.iter().position(|byte| *byte != 0xff)?;
+        if start == 0 {
+            return None;
+        }
+        let marker = bytes[start];
+        bytes = &bytes[start + 1..];
+
+        match marker {
+            // Restart markers and the end of image stand alone, without a length.
+            0x01 | 0xd0..=0xd9 => continue,
+            // Starts of frame, apart from the Huffman table, arithmetic coding and its conditioning markers that share
+            // their range. After the length comes the sample precision, then the height and width.
+            0xc0..=0xcf if marker != 0xc4 && marker != 0xc8 && marker != 0xcc => {
+                let height = be_u16(bytes.get(3..5)?);
+                let width = be_u16(bytes.get(5..7)?);
+                return Some((width as u32, height as u32));
+            }
+            _ => {
+                // The length counts itself, but not the marker.
+                let length = be_u16(bytes.get(0..2)?) as usize;
+                bytes = bytes.get(length..)?;
+            }
+        }
+    }
+}
+
+fn be_u16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+#[test]
+fn image_sizes() {
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+    png.extend_from_slice(&[0, 0, 3, 32, 0, 0, 2, 88]);
+    assert_eq!(parse(&png), Some((800, 600)));
+
+    // An APP0 segment to skip, then a baseline start of frame padded with an extra 0xff.
+    let jpeg = [
+        0xff, 0xd8, 0xff, 0xe0, 0, 4, 0x4a, 0x46, 0xff, 0xff, 0xc0, 0, 17, 8, 0x04, 0x38, 0x07, 0x80,
+    ];
+    assert_eq!(parse(&jpeg), Some((1920, 1080)));
+
+    assert_eq!(parse(&jpeg[..14]), None);
+    assert_eq!(parse(&png[..20]), None);
+    assert_eq!(parse(b"GIF89a"), None);
+
+    // Sizes no real image has.
+    png[16..24].copy_from_slice(&[0, 0, 0, 0, 0, 0, 2, 88]);
+    assert_eq!(parse(&png), None);
+    png[16..24].copy_from_slice(&[255, 255, 255, 255, 0, 0, 2, 88]);
+    assert_eq!(parse(&png), None);
+}
@@ -0,0 +1,12 @@
+//! The rules of the sliding tile puzzle and the solvers for it, with none of the game's rendering, so they can be used
+//! without Amethyst. Build with `default-features = false` to leave Amethyst out.
+
+#![warn(rust_2018_idioms, clippy::all)]
+
+pub mod constructive;
+pub mod history;
+pub mod options;
+pub mod pdb;
+pub mod puzzle;
+pub mod solver;
+pub mod stats;
@@ -2,50 +2,138 @@
 
 use amethyst::prelude::*;
 use amethyst::{
+    config::Config,
     core::*,
     input::InputBundle,
     input::StringBindings,
+    ui::{RenderUi, UiBundle},
     renderer::*,
     renderer::{
         plugins::{RenderFlat2D, RenderToWindow},
         types::DefaultBackend,
     },
     utils::*,
-    LoggerConfig,
+    Error, LoggerConfig,
 };
+use std::path::Path;
+
+use fresh::puzzle::Difficulty;
+use fresh::stats::MoveMetric;
+
+mod cli;
+use crate::cli::Command;
 
 mod states;
 use states::*;
 
+mod animation;
+use crate::animation::Easing;
+
 mod components;
-use crate::components::Tile;
+use crate::components::{Tile, TileNumber};
 
 mod board;
+use crate::board::{NumberStyle, IMAGE};
+
+mod save;
+use crate::save::{LoadError, SavedGame};
+
+mod hud;
+
+mod image_size;
+
+mod menu;
+
+mod systems;
+use crate::systems::{FitBoardSystem, HudSystem, TileNumberSystem};
 
 fn main() -> amethyst::Result<()> {
     amethyst::start_logger(LoggerConfig::default());
 
     let app_root = application_root_dir()?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let setup = match cli::parse(&args).map_err(Error::from_string)? {
+        Command::Play(setup) => setup,
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            return Ok(());
+        }
+    };
+
     let assets_directory = app_root.join("assets");
     let display_config_path = app_root.join("config/display.ron");
     let input_config_path = app_root.join("config/input.ron");
+    let numbers_config_path = app_root.join("config/numbers.ron");
+
+    let number_style = NumberStyle::load(&numbers_config_path).unwrap_or_else(|err| {
+        log::warn!("Couldn't load {}, numbering tiles the default way: {}", numbers_config_path.display(), err);
+        NumberStyle::default()
+    });
 
     let game_data = GameDataBuilder::default()
+        .with(FitBoardSystem::default(), "fit_board_system", &[])
+        .with(HudSystem, "hud_system", &[])
+        .with(TileNumberSystem, "tile_number_system", &["fit_board_system"])
         .with_bundle(TransformBundle::new())?
         .with_bundle(
             InputBundle::<StringBindings>::new().with_bindings_from_file(input_config_path)?,
         )?
+        .with_bundle(UiBundle::<StringBindings>::new())?
         .with_bundle(
             RenderingBundle::<DefaultBackend>::new()
                 .with_plugin(
                     RenderToWindow::from_config_path(display_config_path)?
                         .with_clear([0.34, 0.36, 0.52, 1.0]),
                 )
-                .with_plugin(RenderFlat2D::default()),
+                .with_plugin(RenderFlat2D::default())
+                .with_plugin(RenderUi::default()),
         )?;
-    let mut builder = Application::build(assets_directory, Starting)?;
+    let mut options = GameOptions {
+        tiles_width: 4,
+        tiles_height: 4,
+        image: IMAGE.to_string(),
+        numbered: false,
+        image_shape: false,
+        difficulty: Some(Difficulty::EASY),
+        seed: None,
+        metric: MoveMetric::Tiles,
+        easing: Easing::EaseOut,
+        catchup_speed: 3.0,
+    };
+    setup.apply(&mut options);
+
+    // A game set up on the command line skips the main menu.
+    if setup.is_empty() {
+        return run(&assets_directory, number_style, MainMenu::new(options, load_saved_game()), game_data);
+    }
+    let saved = match (&setup.load, &setup.puzzle) {
+        (Some(path), _) => Some(SavedGame::load(path).map_err(|err| could_not_load(path, err))?),
+        (_, Some(path)) => {
+            let saved = SavedGame::load_puzzle(path, &options.image, options.metric);
+            Some(saved.map_err(|err| could_not_load(path, err))?)
+        }
+        (None, None) => None,
+    };
+    run(&assets_directory, number_style, Starting { options, saved }, game_data)
+}
+
+/// Runs the game, starting from the given state, with numbered tiles styled as given.
+fn run<S: SimpleState + 'static>(
+    assets_directory: &Path,
+    number_style: NumberStyle,
+    state: S,
+    game_data: GameDataBuilder<'static, 'static>,
+) -> amethyst::Result<()> {
+    let mut builder = Application::build(assets_directory, state)?;
     builder.world.register::<Tile>();
+    builder.world.register::<TileNumber>();
+    builder.world.insert(number_style);
     let mut game = builder.build(game_data)?;
     game.run();
     Ok(())
 }
+
+fn could_not_load(path: &Path, err: LoadError) -> Error {
+    Error::from_string(format!("Couldn't load {}: {}", path.display(), err))
+}
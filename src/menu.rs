@@ -0,0 +1,165 @@
+use amethyst::prelude::*;
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    ecs::Entity,
+    input::InputEvent,
+    ui::{
+        get_default_font, Anchor, FontAsset, FontHandle, Interactable, LineMode, UiEvent, UiEventType, UiText,
+        UiTransform,
+    },
+};
+
+pub const TEXT_COLOUR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+/// The colour of the selected line of a menu.
+const SELECTED_COLOUR: [f32; 4] = [1.0, 0.85, 0.4, 1.0];
+
+pub fn default_font(world: &World) -> FontHandle {
+    get_default_font(
+        &world.read_resource::<Loader>(),
+        &world.read_resource::<AssetStorage<FontAsset>>(),
+    )
+}
+
+/// Adds a line of text centred across the screen, `y` above its middle.
+pub fn add_text(world: &mut World, font: &FontHandle, text: String, font_size: f32, y: f32) -> Entity {
+    let transform = UiTransform::new(
+        text.clone(),
+        Anchor::Middle,
+        Anchor::Middle,
+        0.0,
+        y,
+        1.0,
+        600.0,
+        font_size * 1.5,
+    );
+
+    world
+        .create_entity()
+        .with(transform)
+        .with(UiText::new(
+            font.clone(),
+            text,
+            TEXT_COLOUR,
+            font_size,
+            LineMode::Single,
+            Anchor::Middle,
+        ))
+        .build()
+}
+
+/// What the player did with a menu.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MenuEvent<T> {
+    /// The item was clicked, or selected and the select key pressed.
+    Picked(T),
+    /// The item was selected and the left (-1) or right (1) key pressed, to change its setting.
+    Adjusted(T, i32),
+}
+
+/// A list of lines of text, one for each item on a menu. Items can be clicked, or selected with the up and down
+/// keys and picked with the select key.
+pub struct Menu<T> {
+    /// The text entity for each item, top first.
+    lines: Vec<(Entity, T)>,
+    selected: usize,
+}
+
+impl<T> Default for Menu<T> {
+    fn default() -> Self {
+        Menu { lines: vec![], selected: 0 }
+    }
+}
+
+impl<T: Copy + PartialEq> Menu<T> {
+    pub const FONT_SIZE: f32 = 24.0;
+    /// The distance between the middles of each line.
+    const SPACING: f32 = 35.0;
+
+    /// Adds a line for each item to the world, going down from `top`, with the first one selected.
+    pub fn new(world: &mut World, font: &FontHandle, items: Vec<(String, T)>, top: f32) -> Self {
+        let lines = items
+            .into_iter()
+            .enumerate()
+            .map(|(i, (text, item))| {
+                let entity = add_text(world, font, text, Menu::<T>::FONT_SIZE, top - Menu::<T>::SPACING * i as f32);
+                world
+                    .write_storage::<Interactable>()
+                    .insert(entity, Interactable)
+                    .expect("The line was just created");
+                (entity, item)
+            })
+            .collect();
+
+        let menu = Menu { lines, selected: 0 };
+        menu.highlight(world);
+        menu
+    }
+
+    /// Changes the text of an item's line.
+    pub fn set_text(&self, world: &World, item: T, text: String) {
+        let mut texts = world.write_storage::<UiText>();
+        for (entity, _) in self.lines.iter().filter(|(_, it)| *it == item) {
+            if let Some(line) = texts.get_mut(*entity) {
+                line.text = text.clone();
+            }
+        }
+    }
+
+    /// Works out what, if anything, the event does to the menu.
+    pub fn handle_event(&mut self, world: &World, event: &StateEvent) -> Option<MenuEvent<T>> {
+        match event {
+            StateEvent::Ui(UiEvent {
+                event_type: UiEventType::Click,
+                target,
+            }) => {
+                let clicked = self.lines.iter().position(|(entity, _)| entity == target)?;
+                self.select(world, clicked);
+                Some(MenuEvent::Picked(self.lines[clicked].1))
+            }
+            StateEvent::Input(InputEvent::ActionPressed(action)) => {
+                let (_, item) = *self.lines.get(self.selected)?;
+                match action.as_str() {
+                    "move_up" => {
+                        self.select(world, (self.selected + self.lines.len() - 1) % self.lines.len());
+                        None
+                    }
+                    "move_down" => {
+                        self.select(world, (self.selected + 1) % self.lines.len());
+                        None
+                    }
+                    "move_left" => Some(MenuEvent::Adjusted(item, -1)),
+                    "move_right" => Some(MenuEvent::Adjusted(item, 1)),
+                    "menu_select" => Some(MenuEvent::Picked(item)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Removes the menu's lines from the world, unless they've already gone.
+    pub fn delete(&self, world: &mut World) {
+        let entities: Vec<Entity> = self
+            .lines
+            .iter()
+            .map(|(entity, _)| *entity)
+            .filter(|entity| world.is_alive(*entity))
+            .collect();
+        world.delete_entities(&entities).expect("The lines are all alive");
+    }
+
+    fn select(&mut self, world: &World, selected: usize) {
+        self.selected = selected;
+        self.highlight(world);
+    }
+
+    /// Colours the selected line differently from the others.
+    fn highlight(&self, world: &World) {
+        let mut texts = world.write_storage::<UiText>();
+        for (i, (entity, _)) in self.lines.iter().enumerate() {
+            if let Some(line) = texts.get_mut(*entity) {
+                line.color = if i == self.selected { SELECTED_COLOUR } else { TEXT_COLOUR };
+            }
+        }
+    }
+}
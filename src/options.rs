@@ -0,0 +1,99 @@
+//! Reading the command line options shared by the game and the binaries that play without it.
+
+use super::puzzle::{Difficulty, Puzzle};
+
+/// The command line arguments, split up by `parse`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Args {
+    /// Each `--flag` given along with its value, in order.
+    pub flags: Vec<(String, String)>,
+    /// Every other argument, in order.
+    pub rest: Vec<String>,
+}
+
+/// Splits the command line arguments, without the program name, into flags with their values and everything else, or
+/// `None` if help was asked for. The error names a flag without a value, followed by `usage`.
+pub fn parse(args: &[String], usage: &str) -> Result<Option<Args>, String> {
+    let mut parsed = Args::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            parsed.rest.push(arg.clone());
+            continue;
+        }
+
+        // Values can be given as `--width 5` or `--width=5`.
+        let (flag, inline) = match arg.find('=') {
+            Some(idx) => (&arg[..idx], Some(arg[idx + 1..].to_string())),
+            None => (arg.as_str(), None),
+        };
+        if flag == "--help" {
+            return Ok(None);
+        }
+
+        let value = inline
+            .or_else(|| args.next().cloned())
+            .ok_or_else(|| format!("{} needs a value\n\n{}", flag, usage))?;
+        parsed.flags.push((flag.to_string(), value));
+    }
+
+    Ok(Some(parsed))
+}
+
+/// Reads the number of tiles along a side of the board given with `flag`, between `Puzzle::MIN_TILES_DIM` and
+/// `Puzzle::MAX_TILES_DIM`.
+pub fn tiles(flag: &str, value: &str) -> Result<u32, String> {
+    value
+        .parse()
+        .ok()
+        .filter(|tiles| (Puzzle::MIN_TILES_DIM..=Puzzle::MAX_TILES_DIM).contains(tiles))
+        .ok_or_else(|| {
+            format!(
+                "{} {} should be between {} and {} tiles",
+                flag,
+                value,
+                Puzzle::MIN_TILES_DIM,
+                Puzzle::MAX_TILES_DIM
+            )
+        })
+}
+
+/// Reads the seed given with `--seed`.
+pub fn seed(value: &str) -> Result<u64, String> {
+    value.parse().map_err(|_| format!("--seed {} isn't a number", value))
+}
+
+/// Reads one of `Difficulty::NAMED` given with `--difficulty`, ignoring case.
+pub fn difficulty(value: &str) -> Result<Option<Difficulty>, String> {
+    let named = Difficulty::NAMED.iter().find(|(name, _)| name.eq_ignore_ascii_case(value));
+    let (_, difficulty) = named.ok_or_else(|| format!("There's no {} difficulty", value))?;
+    Ok(*difficulty)
+}
+
+#[test]
+fn parse_args() {
+    let parse = |args: &str| parse(&args.split_whitespace().map(str::to_string).collect::<Vec<_>>(), "usage");
+    let flag = |flag: &str, value: &str| (flag.to_string(), value.to_string());
+
+    assert_eq!(parse(""), Ok(Some(Args::default())));
+    assert_eq!(parse("--width 5 --help"), Ok(None));
+    assert_eq!(
+        parse("moves.txt --width=3 --height 2 other"),
+        Ok(Some(Args {
+            flags: vec![flag("--width", "3"), flag("--height", "2")],
+            rest: vec!["moves.txt".to_string(), "other".to_string()],
+        }))
+    );
+    assert_eq!(parse("--seed"), Err("--seed needs a value\n\nusage".to_string()));
+
+    assert_eq!(tiles("--width", "10"), Ok(10));
+    assert_eq!(tiles("--width", "1"), Err("--width 1 should be between 2 and 10 tiles".to_string()));
+    assert!(tiles("--height", "70000").is_err());
+    assert!(tiles("--height", "-3").is_err());
+
+    assert_eq!(seed("42"), Ok(42));
+    assert!(seed("-1").is_err());
+    assert_eq!(difficulty("medium"), Ok(Some(Difficulty::MEDIUM)));
+    assert_eq!(difficulty("Random"), Ok(None));
+    assert_eq!(difficulty("impossible"), Err("There's no impossible difficulty".to_string()));
+}
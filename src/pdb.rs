@@ -0,0 +1,386 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// The directory, relative to the application root, that pattern databases are cached in.
+pub const DIRECTORY: &str = "pdb";
+
+/// Identifies a pattern database file.
+const MAGIC: &[u8; 4] = b"FPDB";
+/// Bumped whenever the file layout or the meaning of its contents changes, so stale caches get regenerated.
+const VERSION: u32 = 1;
+/// Marks arrangements the generator hasn't reached yet.
+const UNSEEN: u8 = u8::MAX;
+
+/// The fewest moves of one group of tiles needed to get them all home, ignoring every other tile, for every
+/// arrangement of the group.
+#[derive(Debug, PartialEq)]
+struct PatternDatabase {
+    tiles: Vec<u32>,
+    /// Indexed by `rank` of the slots the tiles are in.
+    costs: Vec<u8>,
+}
+
+/// Pattern databases over disjoint groups of tiles. Each one only counts moves of its own tiles, so their costs can be
+/// added together into an estimate that never overestimates how many moves a board needs.
+///
+/// Generating them takes a while, so it's done ahead of time with the `gen-pdb` binary and cached on disk.
+#[derive(Debug, PartialEq)]
+pub struct PatternDatabases {
+    dim: u32,
+    databases: Vec<PatternDatabase>,
+    /// For each tile, the database it's part of.
+    owners: Vec<Option<usize>>,
+}
+
+impl PatternDatabases {
+    /// The groups of tiles to build databases over for boards of the given dimension, if there's a default for it:
+    /// 6-6-3 for 4x4 boards and 6-6-6-6 for 5x5 boards, each group a compact block of the board.
+    pub fn partition(dim: u32) -> Option<Vec<Vec<u32>>> {
+        match dim {
+            4 => Some(vec![
+                vec![1, 4, 5, 8, 9, 12],
+                vec![2, 3, 6, 7, 10, 11],
+                vec![13, 14, 15],
+            ]),
+            5 => Some(vec![
+                vec![1, 2, 5, 6, 7, 12],
+                vec![3, 4, 8, 9, 13, 14],
+                vec![10, 11, 15, 16, 20, 21],
+                vec![17, 18, 19, 22, 23, 24],
+            ]),
+            _ => None,
+        }
+    }
+
+    /// Builds a database for each group of tiles by searching outwards from the solved board. Fully deterministic, so
+    /// the same partition always produces the same file.
+    pub fn generate(dim: u32, partition: &[Vec<u32>]) -> Self {
+        let databases = partition
+            .iter()
+            .map(|tiles| {
+                log::info!("Generating the pattern database for tiles {:?}", tiles);
+                PatternDatabase::generate(dim, tiles)
+            })
+            .collect();
+
+        PatternDatabases::new(dim, databases)
+    }
+
+    fn new(dim: u32, databases: Vec<PatternDatabase>) -> Self {
+        let mut owners = vec![None; (dim * dim) as usize];
+        for (idx, database) in databases.iter().enumerate() {
+            for tile in &database.tiles {
+                owners[*tile as usize] = Some(idx);
+            }
+        }
+
+        PatternDatabases { dim, databases, owners }
+    }
+
+    /// Where the databases for boards of the given dimension are cached within `directory`.
+    pub fn path(directory: &Path, dim: u32) -> PathBuf {
+        directory.join(format!("{}x{}.pdb", dim, dim))
+    }
+
+    pub fn dim(&self) -> u32 {
+        self.dim
+    }
+
+    /// How many databases there are.
+    pub fn len(&self) -> usize {
+        self.databases.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.databases.is_empty()
+    }
+
+    /// The database the tile is part of, if any.
+    pub fn owner(&self, tile: u32) -> Option<usize> {
+        self.owners.get(tile as usize).copied().flatten()
+    }
+
+    /// The cost from a single database, given the slot each tile is in.
+    pub fn cost(&self, database: usize, slots: &[usize]) -> u32 {
+        let database = &self.databases[database];
+        let rank = rank(database.tiles.iter().map(|tile| slots[*tile as usize]), (self.dim * self.dim) as usize);
+
+        database.costs[rank] as u32
+    }
+
+    /// Loads the databases for boards of the given dimension from a file written by `save`.
+    pub fn load(path: &Path, dim: u32) -> io::Result<Self> {
+        PatternDatabases::read(&mut BufReader::new(File::open(path)?), dim)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Layout, all integers little endian: the magic bytes, the version, the board dimension and the number of
+    /// databases as `u32`s, then for each database the number of tiles as a `u32`, the tiles as `u32`s, the number of
+    /// costs as a `u64` and finally the costs as single bytes.
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.dim.to_le_bytes())?;
+        writer.write_all(&(self.databases.len() as u32).to_le_bytes())?;
+
+        for database in &self.databases {
+            writer.write_all(&(database.tiles.len() as u32).to_le_bytes())?;
+            for tile in &database.tiles {
+                writer.write_all(&tile.to_le_bytes())?;
+            }
+            writer.write_all(&(database.costs.len() as u64).to_le_bytes())?;
+            writer.write_all(&database.costs)?;
+        }
+
+        Ok(())
+    }
+
+    fn read(reader: &mut impl Read, dim: u32) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a pattern database file".to_string()));
+        }
+
+        let version = read_u32(reader)?;
+        if version != VERSION {
+            return Err(invalid_data(format!(
+                "pattern database file is version {}, expected version {}",
+                version, VERSION
+            )));
+        }
+
+        let file_dim = read_u32(reader)?;
+        if file_dim != dim {
+            return Err(invalid_data(format!(
+                "pattern database file is for {}x{} boards, expected {}x{}",
+                file_dim, file_dim, dim, dim
+            )));
+        }
+
+        let cells = (dim * dim) as usize;
+        let mut seen = vec![false; cells];
+        let mut databases = vec![];
+        for _ in 0..read_u32(reader)? {
+            let mut tiles = vec![];
+            for _ in 0..read_u32(reader)? {
+                let tile = read_u32(reader)?;
+                if tile == 0 || tile as usize >= cells || seen[tile as usize] {
+                    return Err(invalid_data(format!("pattern database has an invalid tile {}", tile)));
+                }
+                seen[tile as usize] = true;
+                tiles.push(tile);
+            }
+
+            let mut len = [0; 8];
+            reader.read_exact(&mut len)?;
+            if u64::from_le_bytes(len) != arrangements(cells, tiles.len()) as u64 {
+                return Err(invalid_data(format!(
+                    "pattern database for tiles {:?} has the wrong number of entries",
+                    tiles
+                )));
+            }
+
+            let mut costs = vec![0; arrangements(cells, tiles.len())];
+            reader.read_exact(&mut costs)?;
+            databases.push(PatternDatabase { tiles, costs });
+        }
+
+        Ok(PatternDatabases::new(dim, databases))
+    }
+}
+
+impl PatternDatabase {
+    /// Breadth first search from the solved board over the slots of the pattern's tiles and the empty slot, one cost
+    /// at a time. Sliding any other tile is free, so everything reachable that way is visited before moving on to the
+    /// next cost.
+    fn generate(dim: u32, tiles: &[u32]) -> Self {
+        let dim = dim as usize;
+        let cells = dim * dim;
+        let mut costs = vec![UNSEEN; arrangements(cells, tiles.len())];
+        let mut visited = vec![0u64; costs.len() * cells / 64 + 1];
+
+        let home: Vec<usize> = tiles.iter().map(|tile| *tile as usize).collect();
+        let mut current = vec![pack(&home, 0)];
+        let mut cost = 0;
+
+        while !current.is_empty() {
+            let mut next = vec![];
+
+            while let Some(state) = current.pop() {
+                let (mut slots, empty) = unpack(state, tiles.len());
+                let rank = rank(slots.iter().copied(), cells);
+
+                let seen = rank * cells + empty;
+                if visited[seen / 64] & (1 << (seen % 64)) != 0 {
+                    continue;
+                }
+                visited[seen / 64] |= 1 << (seen % 64);
+
+                if costs[rank] == UNSEEN {
+                    costs[rank] = cost;
+                }
+
+                for neighbour in neighbours(dim, empty) {
+                    match slots.iter().position(|slot| *slot == neighbour) {
+                        Some(tile) => {
+                            slots[tile] = empty;
+                            next.push(pack(&slots, neighbour));
+                            slots[tile] = neighbour;
+                        }
+                        None => current.push(pack(&slots, neighbour)),
+                    }
+                }
+            }
+
+            current = next;
+            cost += 1;
+        }
+
+        PatternDatabase {
+            tiles: tiles.to_vec(),
+            costs,
+        }
+    }
+}
+
+/// The number of ways to put `tiles` distinct tiles into `cells` slots.
+fn arrangements(cells: usize, tiles: usize) -> usize {
+    (cells - tiles + 1..=cells).product()
+}
+
+/// Numbers the ways of putting distinct tiles into `cells` slots from 0 up to `arrangements`, given the slot each tile
+/// is in. Each slot is counted among only the slots not already taken by earlier tiles.
+fn rank(slots: impl Iterator<Item = usize>, cells: usize) -> usize {
+    let mut rank = 0;
+    let mut taken = 0u64;
+
+    for (idx, slot) in slots.enumerate() {
+        let taken_before = (taken & ((1 << slot) - 1)).count_ones() as usize;
+        rank = rank * (cells - idx) + slot - taken_before;
+        taken |= 1 << slot;
+    }
+
+    rank
+}
+
+/// The slots next to the given one.
+fn neighbours(dim: usize, slot: usize) -> Vec<usize> {
+    let (x, y) = (slot % dim, slot / dim);
+    let mut res = Vec::with_capacity(4);
+    if x > 0 {
+        res.push(slot - 1);
+    }
+    if x + 1 < dim {
+        res.push(slot + 1);
+    }
+    if y > 0 {
+        res.push(slot - dim);
+    }
+    if y + 1 < dim {
+        res.push(slot + dim);
+    }
+    res
+}
+
+/// Packs the empty slot and the slots of up to 9 tiles, 6 bits each, into one number to keep the search's queues small.
+fn pack(slots: &[usize], empty: usize) -> u64 {
+    slots
+        .iter()
+        .rev()
+        .fold(0, |acc, slot| acc << 6 | *slot as u64)
+        << 6
+        | empty as u64
+}
+
+fn unpack(state: u64, tiles: usize) -> (Vec<usize>, usize) {
+    let slots = (0..tiles).map(|idx| (state >> (6 * (idx + 1)) & 0x3f) as usize).collect();
+
+    (slots, (state & 0x3f) as usize)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[test]
+fn ranks() {
+    let cells = 5;
+    let mut ranks = vec![];
+    for a in 0..cells {
+        for b in (0..cells).filter(|b| *b != a) {
+            ranks.push(rank(vec![a, b].into_iter(), cells));
+        }
+    }
+
+    ranks.sort();
+    assert_eq!(ranks, (0..arrangements(cells, 2)).collect::<Vec<_>>());
+}
+
+#[test]
+fn packs() {
+    let slots = vec![0, 15, 3, 63, 7, 1];
+    assert_eq!(unpack(pack(&slots, 12), slots.len()), (slots, 12));
+}
+
+#[test]
+fn generates() {
+    let databases = PatternDatabases::generate(3, &[vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+    assert_eq!(databases.len(), 2);
+    assert_eq!(databases.owner(3), Some(0));
+    assert_eq!(databases.owner(7), Some(1));
+    assert_eq!(databases.owner(0), None);
+
+    // Every arrangement can be reached from the solved board, and only the solved one costs nothing.
+    for database in &databases.databases {
+        assert!(!database.costs.contains(&UNSEEN));
+        assert_eq!(database.costs.iter().filter(|it| **it == 0).count(), 1);
+    }
+
+    let solved: Vec<usize> = (0..9).collect();
+    assert_eq!(databases.cost(0, &solved), 0);
+
+    // Swapping tiles 1 and 2 takes at least a few moves of them, however the other tiles are shuffled around.
+    let mut swapped = solved;
+    swapped.swap(1, 2);
+    assert!(databases.cost(0, &swapped) >= 4);
+    assert_eq!(databases.cost(1, &swapped), 0);
+
+    assert_eq!(databases, PatternDatabases::generate(3, &[vec![1, 2, 3, 4], vec![5, 6, 7, 8]]));
+}
+
+#[test]
+fn round_trip() {
+    let databases = PatternDatabases::generate(3, &[vec![1, 2, 3], vec![4, 5, 6, 7, 8]]);
+
+    let mut bytes = vec![];
+    databases.write(&mut bytes).unwrap();
+    assert_eq!(PatternDatabases::read(&mut &bytes[..], 3).unwrap(), databases);
+
+    let err = PatternDatabases::read(&mut &bytes[..], 4).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut old = bytes.clone();
+    old[4..8].copy_from_slice(&(VERSION + 1).to_le_bytes());
+    let err = PatternDatabases::read(&mut &old[..], 3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let err = PatternDatabases::read(&mut &bytes[..bytes.len() - 1], 3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
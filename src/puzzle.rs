@@ -0,0 +1,825 @@
+use super::solver;
+use std::fmt;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+pub type TileId = u32;
+
+/// A range of lengths, in moves, that the shortest solution to a scrambled board should fall within.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    pub min_moves: u32,
+    pub max_moves: Option<u32>,
+}
+
+impl Difficulty {
+    pub const EASY: Difficulty = Difficulty { min_moves: 10, max_moves: Some(20) };
+    pub const MEDIUM: Difficulty = Difficulty { min_moves: 20, max_moves: Some(30) };
+
+    /// The difficulties players can pick by name, `None` scrambling the board uniformly at random.
+    pub const NAMED: [(&'static str, Option<Difficulty>); 3] = [
+        ("Easy", Some(Difficulty::EASY)),
+        ("Medium", Some(Difficulty::MEDIUM)),
+        ("Random", None),
+    ];
+
+    /// How many random walks to try before settling for one outside of the range.
+    const ATTEMPTS: u32 = 1000;
+}
+
+/// A direction to slide a tile in, as seen on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The rules of the puzzle: which tile is in which slot, and how they can slide. Knows nothing of how it's shown, see
+/// `Board` in the game for that.
+///
+/// Slots numbers:
+/// + - - +
+/// | 2 3 | 1
+/// | 0 1 | 0
+/// + - - + y
+/// x 0 1
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    /// How many tiles wide the board is.
+    tiles_width: u32,
+    /// How many tiles high the board is.
+    tiles_height: u32,
+    tiles: Vec<Option<TileId>>,
+    /// The seed the board was scrambled with. The same seed always produces the same board.
+    seed: u64,
+}
+
+impl Puzzle {
+    /// The fewest tiles a side of a board can have to be played. Boards a single tile wide or high can't be scrambled.
+    pub const MIN_TILES_DIM: u32 = 2;
+    /// The most tiles a side of a board can have to be played.
+    pub const MAX_TILES_DIM: u32 = 10;
+
+    /// Creates a board in the solved state: the empty slot first, followed by every tile in order.
+    pub fn solved(tiles_width: u32, tiles_height: u32, seed: u64) -> Puzzle {
+        let num_tiles = tiles_width * tiles_height;
+        let mut tiles = Vec::with_capacity((num_tiles) as usize);
+        tiles.push(None);
+        for i in 1..num_tiles {
+            tiles.push(Some(i));
+        }
+
+        Puzzle {
+            tiles_width,
+            tiles_height,
+            tiles,
+            seed,
+        }
+    }
+
+    /// Creates a scrambled board. Without a difficulty it's scrambled uniformly at random, see `scramble_to` for what
+    /// happens with one.
+    pub fn scrambled(tiles_width: u32, tiles_height: u32, seed: u64, difficulty: Option<Difficulty>) -> Puzzle {
+        let mut puzzle = Puzzle::solved(tiles_width, tiles_height, seed);
+        match difficulty {
+            Some(difficulty) => puzzle.scramble_to(difficulty),
+            None => puzzle.scramble(),
+        }
+        puzzle
+    }
+
+    /// Creates a board with the tiles in the given slots, eg. from a saved game. The tiles aren't checked, so they
+    /// might not be solvable.
+    pub fn with_tiles(tiles_width: u32, tiles_height: u32, tiles: Vec<Option<TileId>>, seed: u64) -> Puzzle {
+        Puzzle {
+            tiles_width,
+            tiles_height,
+            tiles,
+            seed,
+        }
+    }
+
+    /// Reads a puzzle file: the tiles as they're seen on the screen, a row per line starting from the top, with `0` for
+    /// the empty slot. Solved, a 3x3 board looks like
+    ///
+    /// ```text
+    /// 6 7 8
+    /// 3 4 5
+    /// 0 1 2
+    /// ```
+    ///
+    /// As with `with_tiles` the tiles aren't checked, see `is_permutation` and `is_solvable`. The error says what's
+    /// wrong with the text.
+    pub fn from_text(text: &str) -> Result<Puzzle, String> {
+        let mut rows = vec![];
+        for line in text.lines().filter(|it| !it.trim().is_empty()) {
+            let row = line
+                .split_whitespace()
+                .map(|tile| tile.parse::<TileId>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("\"{}\" isn't a row of tile numbers", line.trim()))?;
+            rows.push(row);
+        }
+
+        let tiles_width = rows.first().map_or(0, Vec::len);
+        if tiles_width == 0 {
+            return Err("there are no tiles".to_string());
+        }
+        if rows.iter().any(|row| row.len() != tiles_width) {
+            return Err("the rows aren't all the same length".to_string());
+        }
+
+        // Slots count up from the bottom row.
+        let tiles = rows
+            .iter()
+            .rev()
+            .flatten()
+            .map(|tile| Some(*tile).filter(|it| *it != 0))
+            .collect();
+        Ok(Puzzle::with_tiles(tiles_width as u32, rows.len() as u32, tiles, 0))
+    }
+
+    /// The slot the given tile is in.
+    pub fn tile_slot(&self, tile: TileId) -> Option<u32> {
+        self.tiles.iter().position(|it| *it == Some(tile)).map(|idx| idx as u32)
+    }
+
+    pub fn move_tile_at(&mut self, idx: u32) {
+        let to = self.empty_adjacent(idx).unwrap();
+
+        let tiles = &mut self.tiles;
+
+        tiles.swap(to as usize, idx as usize)
+    }
+
+    /// The slots of the tiles from the one next to the empty slot up to the given slot, if the given slot holds a tile
+    /// in the same row or column as the empty slot. Sliding them in that order shifts the whole line along by one.
+    pub fn line_to_empty(&self, idx: u32) -> Option<Vec<u32>> {
+        let empty = self.empty_slot();
+        let (ex, ey) = self.idx_xy(empty);
+        let (x, y) = self.check_idx(idx as i32).map(|idx| self.idx_xy(idx))?;
+        if idx == empty || (x != ex && y != ey) {
+            return None;
+        }
+
+        let step = ((x as i32 - ex as i32).signum(), (y as i32 - ey as i32).signum());
+        let mut xy = (ex as i32, ey as i32);
+        let mut slots = vec![];
+        while xy != (x as i32, y as i32) {
+            xy = (xy.0 + step.0, xy.1 + step.1);
+            slots.push(self.xy_idx((xy.0 as u32, xy.1 as u32)));
+        }
+        Some(slots)
+    }
+
+    /// The slot of the tile that would slide into the empty slot by moving in the given direction, if there is one.
+    pub fn tile_moving(&self, direction: Direction) -> Option<u32> {
+        let (x, y) = self.idx_xy(self.empty_slot());
+        let (x, y) = (x as i32, y as i32);
+
+        // Slots count up from the bottom left, so a tile moving up comes from the row below the empty slot.
+        let from = match direction {
+            Direction::Up => (x, y - 1),
+            Direction::Down => (x, y + 1),
+            Direction::Left => (x + 1, y),
+            Direction::Right => (x - 1, y),
+        };
+        self.check_xy(from).map(|xy| self.xy_idx(xy))
+    }
+
+    /// Shuffles the tiles into a uniformly random, solvable, unsolved arrangement, determined entirely by the board's
+    /// seed.
+    pub fn scramble(&mut self) {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+
+        loop {
+            // Fisher-Yates by hand rather than `SliceRandom::shuffle` so that the seed -> board mapping is pinned down
+            // here and can't change out from under shared seeds.
+            for i in (1..self.tiles.len()).rev() {
+                let j = rng.gen_range(0, i as u32 + 1) as usize;
+                self.tiles.swap(i, j);
+            }
+
+            if !self.is_solvable() {
+                // Swapping two tiles flips the permutation's parity without moving the empty slot. That maps the
+                // unsolvable half of the permutations one-to-one onto the solvable half, so the result stays uniform.
+                let mut occupied = (0..self.tiles.len()).filter(|idx| self.tiles[*idx].is_some());
+                let a = occupied.next().expect("A board should have at least two tiles");
+                let b = occupied.next().expect("A board should have at least two tiles");
+                self.tiles.swap(a, b);
+            }
+
+            if !self.is_solved() {
+                break;
+            }
+        }
+    }
+
+    /// Scrambles the board by random walks of the empty slot from the solved board until one lands within the
+    /// difficulty's range. Like `scramble`, the result is determined entirely by the board's seed.
+    ///
+    /// Some ranges can't be reached on small boards (no 3x3 board is more than 31 moves from solved), so after
+    /// `Difficulty::ATTEMPTS` walks the last one is kept regardless. So is one from a range whose `max_moves` is below
+    /// its `min_moves`.
+    pub fn scramble_to(&mut self, difficulty: Difficulty) {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let longest_walk = difficulty
+            .max_moves
+            .unwrap_or_else(|| difficulty.min_moves.saturating_mul(2))
+            .saturating_mul(2)
+            .max(difficulty.min_moves)
+            .max(1);
+
+        for _ in 0..Difficulty::ATTEMPTS {
+            self.tiles = Puzzle::solved(self.tiles_width, self.tiles_height, self.seed).tiles;
+
+            let mut previous = None;
+            for _ in 0..rng.gen_range(difficulty.min_moves, longest_walk.saturating_add(1)) {
+                let empty = self
+                    .tiles
+                    .iter()
+                    .position(|it| it.is_none())
+                    .expect("A board should have an empty slot") as u32;
+
+                // Never step straight back to where the empty slot just was, that only undoes the last move.
+                let choices: Vec<u32> = self
+                    .adj_xy(self.idx_xy(empty))
+                    .into_iter()
+                    .map(|xy| self.xy_idx(xy))
+                    .filter(|idx| Some(*idx) != previous)
+                    .collect();
+
+                self.move_tile_at(choices[rng.gen_range(0, choices.len() as u32) as usize]);
+                previous = Some(empty);
+            }
+
+            if !self.is_solved()
+                && solver::distance_within(self, difficulty.min_moves, difficulty.max_moves)
+            {
+                return;
+            }
+        }
+
+        log::warn!(
+            "Couldn't find a board within {:?} after {} attempts, using the last one",
+            difficulty,
+            Difficulty::ATTEMPTS
+        );
+        if self.is_solved() {
+            self.scramble();
+        }
+    }
+
+    pub fn tiles_width(&self) -> u32 {
+        self.tiles_width
+    }
+
+    pub fn tiles_height(&self) -> u32 {
+        self.tiles_height
+    }
+
+    pub fn tiles(&self) -> &[Option<TileId>] {
+        &self.tiles
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Whether the board can be solved by sliding tiles.
+    ///
+    /// Counts the inversions between tiles, read in slot order. A horizontal move never changes that count, and a
+    /// vertical move jumps a tile over `tiles_width - 1` others. With an odd width the count's parity is therefore
+    /// fixed and must be even; with an even width each vertical move also flips it, so the inversions plus the empty
+    /// slot's distance from its home row (row 0) must be even. The height doesn't come into it.
+    pub fn is_solvable(&self) -> bool {
+        let tiles: Vec<TileId> = self.tiles.iter().filter_map(|it| *it).collect();
+        let inversions = tiles
+            .iter()
+            .enumerate()
+            .map(|(i, a)| tiles[i + 1..].iter().filter(|b| *b < a).count() as u32)
+            .sum::<u32>();
+
+        if self.tiles_width % 2 == 1 {
+            inversions % 2 == 0
+        } else {
+            let empty = self
+                .tiles
+                .iter()
+                .position(|it| it.is_none())
+                .expect("A board should have an empty slot") as u32;
+            let (_, empty_row) = self.idx_xy(empty);
+
+            (inversions + empty_row) % 2 == 0
+        }
+    }
+
+    /// Whether the slots hold every tile exactly once alongside a single empty slot, as they always do unless the
+    /// puzzle came from `with_tiles` or `from_text`.
+    pub fn is_permutation(&self) -> bool {
+        // Tiles are numbered from 1, leaving 0 for the empty slot.
+        let mut seen = vec![false; self.tiles.len()];
+        for tile in &self.tiles {
+            let idx = tile.map_or(0, |tile| tile as usize);
+            if idx >= seen.len() || seen[idx] {
+                return false;
+            }
+            seen[idx] = true;
+        }
+        true
+    }
+
+    pub fn empty_slot(&self) -> u32 {
+        self.tiles
+            .iter()
+            .position(|it| it.is_none())
+            .expect("A board should have an empty slot") as u32
+    }
+
+    pub fn tile_at(&self, slot: u32) -> Option<TileId> {
+        self.tiles.get(slot as usize).and_then(|it| *it)
+    }
+
+    pub fn is_empty(&self, idx: u32) -> bool {
+        self.tiles
+            .get(idx as usize)
+            .map_or(false, |it| it.is_none())
+    }
+
+    pub fn empty_adjacent(&self, idx: u32) -> Option<u32> {
+        let a = self.idx_xy(idx);
+        let b = self.adj_xy(a);
+
+        b.iter()
+            .map(|xy| self.xy_idx(*xy))
+            .filter(|idx| self.is_empty(*idx))
+            .next()
+    }
+
+    pub fn is_solved(&self) -> bool {
+        let mut it = self.tiles.iter();
+        if it
+            .next()
+            .expect("The first element of tiles should exist")
+            .is_some()
+        {
+            false
+        } else {
+            let mut expected = 1;
+            it.all(|index| {
+                if *index == Some(expected) {
+                    expected += 1;
+                    true
+                } else {
+                    false
+                }
+            })
+        }
+    }
+
+    /// How many slots the board has.
+    pub fn slots(&self) -> u32 {
+        self.tiles_width * self.tiles_height
+    }
+
+    fn adj_xy(&self, (x, y): (u32, u32)) -> Vec<(u32, u32)> {
+        let x = x as i32;
+        let y = y as i32;
+
+        let it = vec![(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)];
+
+        let mut res = Vec::with_capacity(4);
+        for xy in it {
+            self.check_xy(xy).map(|xy| res.push(xy));
+        }
+
+        res
+    }
+
+    fn check_x(&self, x: i32) -> Option<u32> {
+        if (0i32..(self.tiles_width as i32)).contains(&x) {
+            Some(x as u32)
+        } else {
+            None
+        }
+    }
+
+    fn check_y(&self, x: i32) -> Option<u32> {
+        if (0i32..(self.tiles_height as i32)).contains(&x) {
+            Some(x as u32)
+        } else {
+            None
+        }
+    }
+
+    /// The given x and y, if they're on the board.
+    pub fn check_xy(&self, (x, y): (i32, i32)) -> Option<(u32, u32)> {
+        self.check_x(x)
+            .and_then(|x| self.check_y(y).map(|y| (x, y)))
+    }
+
+    /// The given slot, if it's on the board.
+    pub fn check_idx(&self, idx: i32) -> Option<u32> {
+        if (0i32..(self.slots() as i32)).contains(&idx) {
+            Some(idx as u32)
+        } else {
+            None
+        }
+    }
+
+    /// The x and y of a slot, counting from the bottom left.
+    pub fn idx_xy(&self, idx: u32) -> (u32, u32) {
+        let y = idx / self.tiles_width;
+        let x = idx % self.tiles_width;
+
+        (x, y)
+    }
+
+    /// The slot at the given x and y.
+    pub fn xy_idx(&self, (x, y): (u32, u32)) -> u32 {
+        x + y * self.tiles_width
+    }
+}
+
+/// Shows the board the way `from_text` reads it, with the numbers lined up.
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self.slots().saturating_sub(1).to_string().len();
+        for row in self.tiles.chunks(self.tiles_width as usize).rev() {
+            let row: Vec<String> = row
+                .iter()
+                .map(|tile| format!("{:>width$}", tile.unwrap_or(0), width = width))
+                .collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn checks() {
+    let board = Puzzle {
+        tiles_width: 2,
+        tiles_height: 2,
+        tiles: vec![None, Some(2), Some(1), Some(3)],
+        seed: 0,
+    };
+
+    assert_eq!(board.check_x(1), Some(1));
+    assert_eq!(board.check_y(0), Some(0));
+    assert_eq!(board.check_idx(3), Some(3));
+    assert_eq!(board.check_xy((1, 1)), Some((1, 1)));
+
+    assert_eq!(board.check_x(3), None);
+    assert_eq!(board.check_y(4), None);
+    assert_eq!(board.check_idx(8), None);
+    assert_eq!(board.check_xy((1, 2)), None);
+
+    assert_eq!(board.check_x(-1), None);
+    assert_eq!(board.check_y(-2), None);
+    assert_eq!(board.check_idx(-2), None);
+    assert_eq!(board.check_xy((-1, -2)), None);
+    assert_eq!(board.check_xy((-1, -2)), None);
+}
+
+#[test]
+fn idx_xy() {
+    let board = Puzzle {
+        tiles_width: 2,
+        tiles_height: 2,
+        tiles: vec![None, Some(2), Some(1), Some(3)],
+        seed: 0,
+    };
+
+    let (x, y) = board.idx_xy(0);
+    assert_eq!(x, 0);
+    assert_eq!(y, 0);
+
+    let (x, y) = board.idx_xy(1);
+    assert_eq!(x, 1);
+    assert_eq!(y, 0);
+
+    let (x, y) = board.idx_xy(2);
+    assert_eq!(x, 0);
+    assert_eq!(y, 1);
+
+    let (x, y) = board.idx_xy(3);
+    assert_eq!(x, 1);
+    assert_eq!(y, 1);
+}
+
+#[test]
+fn xy_idx() {
+    let board = Puzzle {
+        tiles_width: 2,
+        tiles_height: 2,
+        tiles: vec![None, Some(2), Some(1), Some(3)],
+        seed: 0,
+    };
+
+    let idx = board.xy_idx((0, 0));
+    assert_eq!(idx, 0);
+
+    let idx = board.xy_idx((1, 0));
+    assert_eq!(idx, 1);
+
+    let idx = board.xy_idx((0, 1));
+    assert_eq!(idx, 2);
+    let idx = board.xy_idx((1, 1));
+
+    assert_eq!(idx, 3);
+}
+
+#[test]
+fn adj() {
+    let board = Puzzle {
+        tiles_width: 2,
+        tiles_height: 2,
+        tiles: vec![None, Some(2), Some(1), Some(3)],
+        seed: 0,
+    };
+
+    let adj = board.adj_xy((0, 0));
+    assert_eq!(adj.contains(&(1, 0)), true);
+    assert_eq!(adj.contains(&(0, 1)), true);
+    assert_eq!(adj.len(), 2);
+
+    let board = Puzzle {
+        tiles_width: 3,
+        tiles_height: 3,
+        tiles: vec![
+            None, Some(2), Some(1),
+            Some(2), Some(2), Some(1),
+            Some(2), Some(2), Some(1),
+        ],
+        seed: 0,
+    };
+
+    let adj = board.adj_xy((1, 1));
+    assert_eq!(adj.contains(&(1, 0)), true);
+    assert_eq!(adj.contains(&(0, 1)), true);
+    assert_eq!(adj.contains(&(1, 2)), true);
+    assert_eq!(adj.contains(&(2, 1)), true);
+    assert_eq!(adj.len(), 4);
+}
+
+#[test]
+fn tile_moving() {
+    // + - - - +
+    // | 7 8 6 |
+    // | 4 5 3 |
+    // | 1 _ 2 |
+    // + - - - +
+    let board = Puzzle {
+        tiles_width: 3,
+        tiles_height: 3,
+        tiles: vec![
+            Some(1), None, Some(2),
+            Some(4), Some(5), Some(3),
+            Some(7), Some(8), Some(6),
+        ],
+        seed: 0,
+    };
+
+    assert_eq!(board.tile_moving(Direction::Up), None);
+    assert_eq!(board.tile_moving(Direction::Down), Some(4));
+    assert_eq!(board.tile_moving(Direction::Left), Some(2));
+    assert_eq!(board.tile_moving(Direction::Right), Some(0));
+}
+
+#[test]
+fn line_to_empty() {
+    // + - - - +
+    // | 7 8 6 |
+    // | 4 5 3 |
+    // | 1 _ 2 |
+    // + - - - +
+    let board = Puzzle {
+        tiles_width: 3,
+        tiles_height: 3,
+        tiles: vec![
+            Some(1), None, Some(2),
+            Some(4), Some(5), Some(3),
+            Some(7), Some(8), Some(6),
+        ],
+        seed: 0,
+    };
+
+    assert_eq!(board.line_to_empty(0), Some(vec![0]));
+    assert_eq!(board.line_to_empty(4), Some(vec![4]));
+    assert_eq!(board.line_to_empty(7), Some(vec![4, 7]));
+    assert_eq!(board.line_to_empty(1), None);
+    assert_eq!(board.line_to_empty(3), None);
+    assert_eq!(board.line_to_empty(9), None);
+}
+
+#[test]
+fn board_solved() {
+    let board = Puzzle {
+        tiles_width: 2,
+        tiles_height: 2,
+        tiles: vec![None, Some(1), Some(2), Some(3)],
+        seed: 0,
+    };
+
+    assert_eq!(board.is_solved(), true);
+}
+
+#[test]
+fn solvable() {
+    let solved = Puzzle {
+        tiles_width: 2,
+        tiles_height: 2,
+        tiles: vec![None, Some(1), Some(2), Some(3)],
+        seed: 0,
+    };
+    assert!(solved.is_solvable());
+
+    // One vertical move away from solved.
+    let moved = Puzzle {
+        tiles_width: 2,
+        tiles_height: 2,
+        tiles: vec![Some(2), Some(1), None, Some(3)],
+        seed: 0,
+    };
+    assert!(moved.is_solvable());
+
+    let swapped = Puzzle {
+        tiles_width: 2,
+        tiles_height: 2,
+        tiles: vec![None, Some(2), Some(1), Some(3)],
+        seed: 0,
+    };
+    assert!(!swapped.is_solvable());
+
+    let swapped = Puzzle {
+        tiles_width: 3,
+        tiles_height: 3,
+        tiles: vec![
+            None, Some(1), Some(2),
+            Some(3), Some(4), Some(5),
+            Some(6), Some(8), Some(7),
+        ],
+        seed: 0,
+    };
+    assert!(!swapped.is_solvable());
+}
+
+#[test]
+fn solvable_matches_reachable() {
+    use std::collections::HashSet;
+
+    // Every arrangement of a board with the given shape, found by trying every assignment of tiles to slots.
+    let arrangements = |tiles_width: u32, tiles_height: u32| {
+        let slots = (tiles_width * tiles_height) as usize;
+        let mut res = vec![];
+        let mut tiles: Vec<TileId> = (0..slots as TileId).collect();
+        // Heap's algorithm.
+        let mut counters = vec![0; slots];
+        res.push(tiles.clone());
+        let mut i = 0;
+        while i < slots {
+            if counters[i] < i {
+                tiles.swap(if i % 2 == 0 { 0 } else { counters[i] }, i);
+                res.push(tiles.clone());
+                counters[i] += 1;
+                i = 0;
+            } else {
+                counters[i] = 0;
+                i += 1;
+            }
+        }
+        res.into_iter()
+            .map(|tiles| tiles.into_iter().map(|it| Some(it).filter(|it| *it != 0)).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+
+    for &(tiles_width, tiles_height) in &[(2, 2), (3, 2), (2, 3)] {
+        let solved = Puzzle::solved(tiles_width, tiles_height, 0).tiles;
+        let slots = solved.len() as u32;
+        let mut reachable = HashSet::new();
+        let mut frontier = vec![solved];
+        while let Some(tiles) = frontier.pop() {
+            if !reachable.insert(tiles.clone()) {
+                continue;
+            }
+
+            for idx in 0..slots {
+                let mut board = Puzzle::solved(tiles_width, tiles_height, 0);
+                board.tiles = tiles.clone();
+                if !board.is_empty(idx) && board.empty_adjacent(idx).is_some() {
+                    board.move_tile_at(idx);
+                    frontier.push(board.tiles);
+                }
+            }
+        }
+
+        let arrangements = arrangements(tiles_width, tiles_height);
+        for tiles in arrangements.iter() {
+            let mut board = Puzzle::solved(tiles_width, tiles_height, 0);
+            board.tiles = tiles.clone();
+            assert_eq!(board.is_solvable(), reachable.contains(tiles), "{:?}", tiles);
+        }
+        assert_eq!(reachable.len() * 2, arrangements.len());
+    }
+}
+
+#[test]
+fn scramble() {
+    let scrambled = |tiles_width: u32, tiles_height: u32, seed: u64| {
+        let mut board = Puzzle::solved(tiles_width, tiles_height, seed);
+        board.scramble();
+        board
+    };
+
+    for tiles_width in 2..6 {
+        for tiles_height in 2..6 {
+            for seed in 0..20 {
+                let board = scrambled(tiles_width, tiles_height, seed);
+                assert!(board.is_solvable());
+                assert!(!board.is_solved());
+                assert_eq!(board.tiles, scrambled(tiles_width, tiles_height, seed).tiles);
+            }
+        }
+    }
+
+    assert_ne!(scrambled(4, 4, 1).tiles, scrambled(4, 4, 2).tiles);
+
+    // Seeds are shared between players, so the board a seed produces must never change.
+    assert_eq!(
+        scrambled(3, 3, 42).tiles,
+        vec![
+            None, Some(4), Some(3),
+            Some(6), Some(8), Some(7),
+            Some(1), Some(5), Some(2),
+        ]
+    );
+}
+
+#[test]
+fn scramble_to() {
+    let difficulty = Difficulty {
+        min_moves: 10,
+        max_moves: Some(14),
+    };
+    let scrambled = |tiles_dim: u32, seed: u64| {
+        let mut board = Puzzle::solved(tiles_dim, tiles_dim, seed);
+        board.scramble_to(difficulty);
+        board
+    };
+
+    for seed in 0..5 {
+        let board = scrambled(3, seed);
+        assert!(solver::distance_within(&board, 10, Some(14)));
+        assert_eq!(board.tiles, scrambled(3, seed).tiles);
+    }
+
+    let board = scrambled(4, 0);
+    assert!(solver::distance_within(&board, 10, Some(14)));
+
+    // Out of reach on a 2x2 board, which is never more than 6 moves from solved.
+    let mut board = Puzzle::solved(2, 2, 0);
+    board.scramble_to(Difficulty {
+        min_moves: 10,
+        max_moves: None,
+    });
+    assert!(board.is_solvable());
+    assert!(!board.is_solved());
+
+    // An empty range still gives a scrambled board.
+    let mut board = Puzzle::solved(3, 3, 0);
+    board.scramble_to(Difficulty {
+        min_moves: 50,
+        max_moves: Some(10),
+    });
+    assert!(board.is_solvable());
+    assert!(!board.is_solved());
+}
+
+#[test]
+fn text() {
+    let puzzle = Puzzle::from_text("6 7 8\n3 4 5\n1 0 2\n\n").unwrap();
+    assert_eq!((puzzle.tiles_width(), puzzle.tiles_height()), (3, 3));
+    assert_eq!(
+        puzzle.tiles(),
+        &[Some(1), None, Some(2), Some(3), Some(4), Some(5), Some(6), Some(7), Some(8)]
+    );
+    assert_eq!(puzzle.to_string(), "6 7 8\n3 4 5\n1 0 2\n");
+
+    let mut wide = Puzzle::solved(4, 3, 0);
+    wide.scramble();
+    assert_eq!(wide.to_string().lines().next().map(str::len), Some(11));
+    assert_eq!(Puzzle::from_text(&wide.to_string()).unwrap().tiles(), wide.tiles());
+
+    assert_eq!(Puzzle::from_text("1 2\n3 x").err(), Some("\"3 x\" isn't a row of tile numbers".to_string()));
+    assert_eq!(Puzzle::from_text("1 2 3\n0 4").err(), Some("the rows aren't all the same length".to_string()));
+    assert_eq!(Puzzle::from_text(" \n").err(), Some("there are no tiles".to_string()));
+
+    assert!(Puzzle::solved(3, 2, 0).is_permutation());
+    assert!(!Puzzle::from_text("1 2\n0 4").unwrap().is_permutation());
+    assert!(!Puzzle::from_text("1 1\n0 2").unwrap().is_permutation());
+    assert!(!Puzzle::from_text("1 2\n0 0").unwrap().is_permutation());
+}
@@ -0,0 +1,342 @@
+use amethyst::config::{Config, ConfigError};
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use fresh::history::{History, Slide};
+use fresh::puzzle::{Puzzle, TileId};
+use fresh::stats::{MoveMetric, Stats};
+
+use super::states::GameOptions;
+
+/// The directory games are saved in, relative to the application root.
+pub const DIRECTORY: &str = "saves";
+/// Bumped whenever the layout of a saved game changes, so older saves are turned away rather than misread.
+const VERSION: u32 = 2;
+
+/// Everything needed to pick a game back up where the player left it, saved as RON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    /// The `VERSION` the game was saved with.
+    pub version: u32,
+    pub tiles_width: u32,
+    pub tiles_height: u32,
+    /// The tile in each slot, as in `Puzzle::tiles`.
+    pub tiles: Vec<Option<TileId>>,
+    /// The image the tiles were cut from, as in `GameOptions::image`.
+    pub image: String,
+    /// The seed the board was scrambled with.
+    pub seed: u64,
+    pub history: History,
+    /// How `moves` were counted.
+    pub metric: MoveMetric,
+    pub moves: u32,
+    pub hints: u32,
+    pub elapsed: Duration,
+}
+
+/// Just enough of a saved game to tell which version saved it, whatever else has changed since.
+#[derive(Serialize, Deserialize)]
+struct Version {
+    version: u32,
+}
+
+/// Why a saved game couldn't be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read, or isn't a saved game.
+    Config(ConfigError),
+    /// The puzzle file couldn't be read.
+    Io(io::Error),
+    /// The puzzle isn't a grid of tile numbers, the reason is given.
+    Puzzle(String),
+    /// The game was saved by a version of the game that saves them differently.
+    Version(u32),
+    /// The board's dimensions are out of range, or don't match the number of slots.
+    Dimensions { tiles_width: u32, tiles_height: u32, slots: usize },
+    /// The slots don't hold every tile exactly once alongside a single empty slot.
+    NotPermutation,
+    /// No sequence of moves solves the board.
+    Unsolvable,
+    /// The history has a slide that can't be made when its moves are undone and redone on the board.
+    History,
+    /// Fewer moves were counted than the history has made.
+    Moves,
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Config(err) => write!(f, "{}", err),
+            LoadError::Io(err) => write!(f, "{}", err),
+            LoadError::Puzzle(reason) => write!(f, "not a puzzle, {}", reason),
+            LoadError::Version(version) => {
+                write!(f, "saved with version {}, but only version {} can be loaded", version, VERSION)
+            }
+            LoadError::Dimensions { tiles_width, tiles_height, slots } => write!(
+                f,
+                "a {}x{} board can't have {} slots, or be played on",
+                tiles_width, tiles_height, slots
+            ),
+            LoadError::NotPermutation => write!(f, "the board doesn't hold every tile exactly once"),
+            LoadError::Unsolvable => write!(f, "the board can't be solved"),
+            LoadError::History => write!(f, "the move history doesn't fit the board"),
+            LoadError::Moves => write!(f, "fewer moves were counted than the history has made"),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<ConfigError> for LoadError {
+    fn from(err: ConfigError) -> Self {
+        LoadError::Config(err)
+    }
+}
+
+impl SavedGame {
+    /// Captures the game in progress.
+    pub fn new(puzzle: &Puzzle, image: &str, metric: MoveMetric, history: &History, stats: &Stats) -> SavedGame {
+        SavedGame {
+            version: VERSION,
+            tiles_width: puzzle.tiles_width(),
+            tiles_height: puzzle.tiles_height(),
+            tiles: puzzle.tiles().to_vec(),
+            image: image.to_string(),
+            seed: puzzle.seed(),
+            history: history.clone(),
+            metric,
+            moves: stats.moves,
+            hints: stats.hints,
+            elapsed: stats.elapsed,
+        }
+    }
+
+    /// Reads a puzzle, see `Puzzle::from_text`, to play with the given image and way of counting moves.
+    pub fn from_puzzle(text: &str, image: &str, metric: MoveMetric) -> Result<SavedGame, LoadError> {
+        let puzzle = Puzzle::from_text(text).map_err(LoadError::Puzzle)?;
+        let saved = SavedGame::new(&puzzle, image, metric, &History::default(), &Stats::default());
+        saved.validate()?;
+        Ok(saved)
+    }
+
+    /// Loads a puzzle file, see `from_puzzle`.
+    pub fn load_puzzle(path: &Path, image: &str, metric: MoveMetric) -> Result<SavedGame, LoadError> {
+        SavedGame::from_puzzle(&fs::read_to_string(path).map_err(LoadError::Io)?, image, metric)
+    }
+
+    /// Where the game is saved within `directory`.
+    pub fn path(directory: &Path) -> PathBuf {
+        directory.join("game.ron")
+    }
+
+    /// Loads a game saved by `save`, making sure it's one that can be played.
+    pub fn load(path: &Path) -> Result<SavedGame, LoadError> {
+        let Version { version } = Version::load(path)?;
+        if version != VERSION {
+            return Err(LoadError::Version(version));
+        }
+
+        let saved = <SavedGame as Config>::load(path)?;
+        saved.validate()?;
+        Ok(saved)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ConfigError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(ConfigError::File)?;
+        }
+
+        Config::write(self, path)
+    }
+
+    /// Checks the saved board is one the game can play, and that the history and moves fit it.
+    pub fn validate(&self) -> Result<(), LoadError> {
+        let dims = GameOptions::MIN_TILES_DIM..=GameOptions::MAX_TILES_DIM;
+        let slots = self.tiles.len();
+        if !dims.contains(&self.tiles_width)
+            || !dims.contains(&self.tiles_height)
+            || slots != (self.tiles_width * self.tiles_height) as usize
+        {
+            return Err(LoadError::Dimensions {
+                tiles_width: self.tiles_width,
+                tiles_height: self.tiles_height,
+                slots,
+            });
+        }
+
+        let puzzle = self.puzzle();
+        if !puzzle.is_permutation() {
+            return Err(LoadError::NotPermutation);
+        }
+
+        if !puzzle.is_solvable() {
+            return Err(LoadError::Unsolvable);
+        }
+
+        // Undoing every move made and then redoing every move undone makes each slide in the history once, just as
+        // the player could.
+        let mut puzzle = puzzle;
+        let mut history = self.history.clone();
+        while let Some(slides) = history.next_undo() {
+            make_slides(&mut puzzle, &slides)?;
+            history.undo();
+        }
+        while let Some(slides) = history.next_redo() {
+            make_slides(&mut puzzle, &slides)?;
+            history.redo();
+        }
+
+        // Undoing a move takes its moves off the count, which can't go below zero.
+        let made: u32 = self.history.made().map(|slides| self.metric.count(slides.len())).sum();
+        if self.moves < made {
+            return Err(LoadError::Moves);
+        }
+
+        Ok(())
+    }
+
+    /// The saved board.
+    pub fn puzzle(&self) -> Puzzle {
+        Puzzle::with_tiles(self.tiles_width, self.tiles_height, self.tiles.clone(), self.seed)
+    }
+
+    /// How the player was getting on. The clock carries on from the first move after loading.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            moves: self.moves,
+            hints: self.hints,
+            elapsed: self.elapsed,
+            ..Stats::default()
+        }
+    }
+}
+
+/// Makes the slides on the board, checking each one moves the tile it says from next to the empty slot into it.
+fn make_slides(puzzle: &mut Puzzle, slides: &[Slide]) -> Result<(), LoadError> {
+    for slide in slides {
+        if slide.to != puzzle.empty_slot()
+            || puzzle.tile_at(slide.from) != Some(slide.tile)
+            || puzzle.empty_adjacent(slide.from) != Some(slide.to)
+        {
+            return Err(LoadError::History);
+        }
+        puzzle.move_tile_at(slide.from);
+    }
+    Ok(())
+}
+
+#[test]
+fn validate() {
+    let mut puzzle = Puzzle::solved(4, 3, 7);
+    puzzle.scramble();
+    let saved = SavedGame::new(&puzzle, "background.jpg", MoveMetric::Tiles, &History::default(), &Stats::default());
+    assert_eq!(saved.validate().ok(), Some(()));
+    assert_eq!(saved.puzzle().tiles(), puzzle.tiles());
+
+    let corrupt = |corrupt: &dyn Fn(&mut SavedGame)| {
+        let mut saved = saved.clone();
+        corrupt(&mut saved);
+        saved.validate().err().map(|err| err.to_string())
+    };
+    let error = |err: LoadError| Some(err.to_string());
+
+    let dimensions = error(LoadError::Dimensions { tiles_width: 3, tiles_height: 3, slots: 12 });
+    assert_eq!(corrupt(&|saved| saved.tiles_width = 3), dimensions);
+    let dimensions = error(LoadError::Dimensions { tiles_width: 1, tiles_height: 12, slots: 12 });
+    assert_eq!(
+        corrupt(&|saved| {
+            saved.tiles_width = 1;
+            saved.tiles_height = 12;
+        }),
+        dimensions
+    );
+
+    let not_permutation = error(LoadError::NotPermutation);
+    assert_eq!(corrupt(&|saved| saved.tiles[0] = saved.tiles[1]), not_permutation);
+    assert_eq!(corrupt(&|saved| *saved.tiles.iter_mut().find(|it| it.is_none()).unwrap() = Some(12)), not_permutation);
+
+    // Swapping two tiles flips the board's parity.
+    let unsolvable = error(LoadError::Unsolvable);
+    assert_eq!(
+        corrupt(&|saved| {
+            let tiles: Vec<usize> = (0..12).filter(|idx| saved.tiles[*idx].is_some()).take(2).collect();
+            saved.tiles.swap(tiles[0], tiles[1]);
+        }),
+        unsolvable
+    );
+
+    let mut history = History::default();
+    history.record(vec![Slide { from: 12, to: 11, tile: 1 }]);
+    assert_eq!(corrupt(&|saved| saved.history = history.clone()), error(LoadError::History));
+
+    // A history that fits the board: a tile slid into the empty slot and back out again.
+    let empty = puzzle.empty_slot();
+    let next = (0..12).find(|slot| puzzle.empty_adjacent(*slot).is_some()).unwrap();
+    let slide = Slide { from: next, to: empty, tile: puzzle.tile_at(next).unwrap() };
+    let mut history = History::default();
+    history.record(vec![slide]);
+    history.record(vec![slide.reversed()]);
+    let made = |saved: &mut SavedGame| {
+        saved.history = history.clone();
+        saved.moves = 2;
+    };
+    assert_eq!(corrupt(&made), None);
+
+    // Slides between slots on the board, but ones that can't be made: the tile isn't next to the empty slot, or isn't
+    // the tile in the slot it slides from.
+    let far = (0..12).find(|slot| puzzle.empty_adjacent(*slot).is_none() && *slot != empty).unwrap();
+    let mut history = History::default();
+    history.record(vec![Slide { from: far, to: empty, tile: puzzle.tile_at(far).unwrap() }]);
+    history.undo();
+    assert_eq!(corrupt(&|saved| saved.history = history.clone()), error(LoadError::History));
+    let mut history = History::default();
+    history.record(vec![Slide { tile: slide.tile + 1, ..slide }]);
+    history.undo();
+    assert_eq!(corrupt(&|saved| saved.history = history.clone()), error(LoadError::History));
+
+    // A line of two tiles slid at once counts as two moves, or as one when counting lines.
+    let line = (0..12).find_map(|slot| Slide::line(&puzzle, slot).filter(|line| line.len() == 2)).unwrap();
+    let mut slid = puzzle.clone();
+    make_slides(&mut slid, &line).unwrap();
+    let mut history = History::default();
+    history.record(line);
+    let counted = |metric: MoveMetric, moves: u32| {
+        corrupt(&|saved| {
+            saved.tiles = slid.tiles().to_vec();
+            saved.history = history.clone();
+            saved.metric = metric;
+            saved.moves = moves;
+        })
+    };
+    assert_eq!(counted(MoveMetric::Tiles, 2), None);
+    assert_eq!(counted(MoveMetric::Lines, 1), None);
+    assert_eq!(counted(MoveMetric::Tiles, 1), error(LoadError::Moves));
+    assert_eq!(counted(MoveMetric::Lines, 0), error(LoadError::Moves));
+}
+
+#[test]
+fn puzzle() {
+    let saved = SavedGame::from_puzzle("6 7 8\n3 4 5\n1 0 2\n\n", "background.jpg", MoveMetric::Tiles).unwrap();
+    assert_eq!((saved.tiles_width, saved.tiles_height), (3, 3));
+    assert_eq!(
+        saved.tiles,
+        vec![Some(1), None, Some(2), Some(3), Some(4), Some(5), Some(6), Some(7), Some(8)]
+    );
+
+    let wide = SavedGame::from_puzzle("4 5 6 7\n0 1 2 3", "background.jpg", MoveMetric::Tiles).unwrap();
+    assert!(wide.puzzle().is_solved());
+
+    let error = |text: &str| {
+        let saved = SavedGame::from_puzzle(text, "background.jpg", MoveMetric::Tiles);
+        saved.err().map(|err| err.to_string())
+    };
+    assert_eq!(error("1 2\n3 x"), Some("not a puzzle, \"3 x\" isn't a row of tile numbers".to_string()));
+    assert_eq!(error("1 2 3\n0 4"), Some("not a puzzle, the rows aren't all the same length".to_string()));
+    assert_eq!(error("1 2\n0 4"), Some(LoadError::NotPermutation.to_string()));
+    assert_eq!(error("2 1\n0 3"), Some(LoadError::Unsolvable.to_string()));
+}
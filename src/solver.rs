@@ -0,0 +1,440 @@
+use super::puzzle::*;
+use super::pdb::PatternDatabases;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Limits on how much work a search may do before giving up.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// The most positions the search may visit, or `None` to search for as long as it takes.
+    pub node_budget: Option<u64>,
+    /// Lets another thread stop the search by setting it to `true`.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Limits {
+    /// How many positions are visited between checks of the cancellation flag. Must be a power of two.
+    const CANCEL_CHECK_INTERVAL: u64 = 1 << 12;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolveError {
+    /// No sequence of moves solves the board.
+    Unsolvable,
+    /// The search visited its whole node budget without finding a solution.
+    OutOfBudget,
+    /// The search was cancelled.
+    Cancelled,
+}
+
+/// Finds a shortest sequence of moves that solves the board.
+///
+/// Each move is the slot of the tile to slide into the empty slot, so they can be handed to `Puzzle::move_tile_at`
+/// one after another. Runs IDA* with the Manhattan distance plus linear conflict heuristic, which comfortably solves
+/// 3x3 boards and most 4x4 boards within a few million nodes. Pattern databases for the board's dimension, if given,
+/// tighten the heuristic enough to solve 4x4 boards quickly and 5x5 boards at all; ones for any other dimension, or
+/// for a board that isn't square, are ignored.
+pub fn solve(
+    board: &Puzzle,
+    databases: Option<&PatternDatabases>,
+    limits: &Limits,
+) -> Result<Vec<u32>, SolveError> {
+    if !board.is_solvable() {
+        return Err(SolveError::Unsolvable);
+    }
+
+    let databases =
+        databases.filter(|it| it.dim() == board.tiles_width() && it.dim() == board.tiles_height());
+    let mut search = Search::new(board, databases, limits);
+    let mut bound = search.estimate;
+
+    loop {
+        match search.bounded(0, bound, None)? {
+            Bounded::Found => {
+                search.path.reverse();
+                return Ok(search.path);
+            }
+            Bounded::Exceeded(next) => bound = next,
+        }
+    }
+}
+
+/// How many positions `distance_within` visits before giving up on a board.
+const DISTANCE_NODE_BUDGET: u64 = 1_000_000;
+
+/// Whether the shortest solution to the board is at least `min_moves` and, if given, at most `max_moves` moves long.
+///
+/// Runs the same search as `solve`, but only as far as it needs to: a board is known to be at least `min_moves`
+/// away as soon as a search bounded below that fails, which is much cheaper than finding the solution. Boards too far
+/// from solved to tell within `DISTANCE_NODE_BUDGET` positions don't count as within the range.
+pub fn distance_within(board: &Puzzle, min_moves: u32, max_moves: Option<u32>) -> bool {
+    let limits = Limits {
+        node_budget: Some(DISTANCE_NODE_BUDGET),
+        cancel: None,
+    };
+    distance_within_limits(board, min_moves, max_moves, &limits)
+}
+
+fn distance_within_limits(board: &Puzzle, min_moves: u32, max_moves: Option<u32>, limits: &Limits) -> bool {
+    let mut search = Search::new(board, None, limits);
+    let mut bound = search.estimate;
+
+    loop {
+        match max_moves {
+            Some(max) if bound > max => return false,
+            None if bound >= min_moves => return true,
+            _ => {}
+        }
+
+        match search.bounded(0, bound, None) {
+            Ok(Bounded::Found) => return bound >= min_moves,
+            Ok(Bounded::Exceeded(next)) => bound = next,
+            Err(_) => return false,
+        }
+    }
+}
+
+enum Bounded {
+    /// A solution within the bound was found.
+    Found,
+    /// No solution within the bound exists, the smallest estimate that went over it is given.
+    Exceeded(u32),
+}
+
+/// The state of a search over a board, as plain slot indices with 0 standing in for the empty slot.
+struct Search<'a> {
+    width: usize,
+    height: usize,
+    tiles: Vec<u32>,
+    empty: usize,
+    /// The linear conflicts in each row, see `Search::conflicts`.
+    row_conflicts: Vec<u32>,
+    /// The linear conflicts in each column.
+    col_conflicts: Vec<u32>,
+    manhattan: u32,
+    /// The slot each tile is in.
+    slots: Vec<usize>,
+    databases: Option<&'a PatternDatabases>,
+    /// The cost from each of the pattern databases.
+    pattern_costs: Vec<u32>,
+    /// The heuristic estimate of the number of moves left, kept up to date as tiles slide.
+    estimate: u32,
+    /// The moves leading to a solution, last move first, once one has been found.
+    path: Vec<u32>,
+    nodes: u64,
+    limits: &'a Limits,
+}
+
+impl<'a> Search<'a> {
+    fn new(board: &Puzzle, databases: Option<&'a PatternDatabases>, limits: &'a Limits) -> Self {
+        let tiles: Vec<u32> = board.tiles().iter().map(|it| it.unwrap_or(0)).collect();
+        let empty = tiles.iter().position(|it| *it == 0).expect("A board should have an empty slot");
+        let (width, height) = (board.tiles_width() as usize, board.tiles_height() as usize);
+
+        let mut slots = vec![0; tiles.len()];
+        for (slot, tile) in tiles.iter().enumerate() {
+            slots[*tile as usize] = slot;
+        }
+        let pattern_costs = databases.map_or(vec![], |databases| {
+            (0..databases.len()).map(|idx| databases.cost(idx, &slots)).collect()
+        });
+
+        let mut search = Search {
+            width,
+            height,
+            tiles,
+            empty,
+            row_conflicts: vec![0; height],
+            col_conflicts: vec![0; width],
+            manhattan: 0,
+            slots,
+            databases,
+            pattern_costs,
+            estimate: 0,
+            path: vec![],
+            nodes: 0,
+            limits,
+        };
+
+        search.manhattan = search
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, tile)| **tile != 0)
+            .map(|(slot, tile)| search.distance(*tile, slot))
+            .sum();
+        for row in 0..height {
+            search.row_conflicts[row] = search.conflicts(row, true);
+        }
+        for col in 0..width {
+            search.col_conflicts[col] = search.conflicts(col, false);
+        }
+        search.update_estimate();
+        search
+    }
+
+    /// How many slots away from home the tile would be in the given slot.
+    fn distance(&self, tile: u32, slot: usize) -> u32 {
+        let home = tile as usize;
+        let dx = (home % self.width) as i32 - (slot % self.width) as i32;
+        let dy = (home / self.width) as i32 - (slot / self.width) as i32;
+
+        (dx.abs() + dy.abs()) as u32
+    }
+
+    /// The fewest tiles that have to leave the given row (or column) so that the tiles in it which belong to it are in
+    /// order. Each of those tiles needs at least two moves on top of its Manhattan distance to step aside and back.
+    ///
+    /// That's the number of such tiles minus the longest run of them, not necessarily contiguous, already in order.
+    fn conflicts(&self, line: usize, row: bool) -> u32 {
+        let len = if row { self.width } else { self.height };
+        let homes: Vec<usize> = (0..len)
+            .map(|i| if row { line * self.width + i } else { i * self.width + line })
+            .map(|slot| self.tiles[slot] as usize)
+            .filter(|tile| *tile != 0)
+            .filter(|tile| if row { tile / self.width == line } else { tile % self.width == line })
+            .collect();
+
+        let mut longest = vec![1; homes.len()];
+        for i in 0..homes.len() {
+            for j in 0..i {
+                if homes[j] < homes[i] {
+                    longest[i] = longest[i].max(longest[j] + 1);
+                }
+            }
+        }
+
+        (homes.len() - longest.into_iter().max().unwrap_or(0)) as u32
+    }
+
+    /// Both the linear conflict and the pattern database estimates are admissible, so the larger of the two is too.
+    fn update_estimate(&mut self) {
+        let conflicts: u32 = self.row_conflicts.iter().chain(self.col_conflicts.iter()).sum();
+        let patterns: u32 = self.pattern_costs.iter().sum();
+        self.estimate = (self.manhattan + 2 * conflicts).max(patterns);
+    }
+
+    /// The slots the empty slot can swap with, never undoing the previous move.
+    fn neighbours(&self, previous: Option<usize>) -> Vec<usize> {
+        let (x, y) = (self.empty % self.width, self.empty / self.width);
+        let mut res = Vec::with_capacity(4);
+        if x > 0 {
+            res.push(self.empty - 1);
+        }
+        if x + 1 < self.width {
+            res.push(self.empty + 1);
+        }
+        if y > 0 {
+            res.push(self.empty - self.width);
+        }
+        if y + 1 < self.height {
+            res.push(self.empty + self.width);
+        }
+        res.retain(|slot| Some(*slot) != previous);
+        res
+    }
+
+    /// Slides the tile at `slot` into the empty slot.
+    fn slide(&mut self, slot: usize) {
+        let tile = self.tiles[slot];
+        let to = self.empty;
+        self.manhattan = self.manhattan + self.distance(tile, to) - self.distance(tile, slot);
+        self.tiles.swap(slot, to);
+        self.empty = slot;
+        self.slots[tile as usize] = to;
+        self.slots[0] = slot;
+
+        if let Some(databases) = self.databases {
+            if let Some(owner) = databases.owner(tile) {
+                self.pattern_costs[owner] = databases.cost(owner, &self.slots);
+            }
+        }
+
+        // Sliding along a row keeps the order of the tiles in it, but moves the tile between two columns, and the
+        // other way around.
+        if slot / self.width == to / self.width {
+            self.col_conflicts[slot % self.width] = self.conflicts(slot % self.width, false);
+            self.col_conflicts[to % self.width] = self.conflicts(to % self.width, false);
+        } else {
+            self.row_conflicts[slot / self.width] = self.conflicts(slot / self.width, true);
+            self.row_conflicts[to / self.width] = self.conflicts(to / self.width, true);
+        }
+        self.update_estimate();
+    }
+
+    /// Depth first search for a solution no longer than `bound`, having already made `cost` moves.
+    fn bounded(&mut self, cost: u32, bound: u32, previous: Option<usize>) -> Result<Bounded, SolveError> {
+        self.nodes += 1;
+        if self.nodes > self.limits.node_budget.unwrap_or(u64::MAX) {
+            return Err(SolveError::OutOfBudget);
+        }
+        if self.nodes & (Limits::CANCEL_CHECK_INTERVAL - 1) == 0 {
+            if let Some(cancel) = &self.limits.cancel {
+                if cancel.load(Ordering::Relaxed) {
+                    return Err(SolveError::Cancelled);
+                }
+            }
+        }
+
+        let total = cost + self.estimate;
+        if total > bound {
+            return Ok(Bounded::Exceeded(total));
+        }
+        if self.estimate == 0 {
+            return Ok(Bounded::Found);
+        }
+
+        let mut next = u32::MAX;
+        for slot in self.neighbours(previous) {
+            let empty = self.empty;
+            self.slide(slot);
+            let res = self.bounded(cost + 1, bound, Some(empty));
+            self.slide(empty);
+
+            match res? {
+                Bounded::Found => {
+                    self.path.push(slot as u32);
+                    return Ok(Bounded::Found);
+                }
+                Bounded::Exceeded(total) => next = next.min(total),
+            }
+        }
+
+        Ok(Bounded::Exceeded(next))
+    }
+}
+
+#[test]
+fn within() {
+    let mut board = Puzzle::solved(3, 3, 0);
+    assert!(distance_within(&board, 0, Some(0)));
+    assert!(!distance_within(&board, 1, None));
+
+    // Each of these moves takes a tile further from home, so the board is exactly three moves from solved.
+    board.move_tile_at(1);
+    board.move_tile_at(2);
+    board.move_tile_at(5);
+    assert!(distance_within(&board, 3, Some(3)));
+    assert!(distance_within(&board, 2, None));
+    assert!(distance_within(&board, 0, Some(10)));
+    assert!(!distance_within(&board, 0, Some(2)));
+    assert!(!distance_within(&board, 4, None));
+}
+
+#[test]
+fn linear_conflict() {
+    let limits = Limits::default();
+    let search = Search::new(&Puzzle::solved(3, 3, 0), None, &limits);
+    assert_eq!(search.estimate, 0);
+
+    // Keeping the estimate up to date as tiles slide gives the same result as working it out from scratch.
+    let moves = [3, 4, 1, 2, 5, 4, 3, 0, 1, 2, 5, 4, 1];
+    let mut search = Search::new(&Puzzle::solved(3, 3, 0), None, &limits);
+    let mut board = Puzzle::solved(3, 3, 0);
+    for slot in moves.iter() {
+        search.slide(*slot as usize);
+        board.move_tile_at(*slot);
+
+        let fresh = Search::new(&board, None, &limits);
+        assert_eq!(search.tiles, fresh.tiles);
+        assert_eq!(search.manhattan, fresh.manhattan);
+        assert_eq!(search.estimate, fresh.estimate);
+    }
+
+    // Tiles 1 and 2, and tiles 4 and 5, are in their home rows but the wrong way around.
+    assert_eq!(
+        board.tiles()[..6],
+        [Some(2), None, Some(1), Some(3), Some(5), Some(4)]
+    );
+    assert_eq!(search.row_conflicts, vec![1, 1, 0]);
+    assert_eq!(search.col_conflicts, vec![0, 0, 0]);
+    assert_eq!(search.estimate, search.manhattan + 4);
+}
+
+#[test]
+fn solves() {
+    let limits = Limits::default();
+    for seed in 0..10 {
+        let mut board = Puzzle::solved(3, 3, seed);
+        board.scramble_to(Difficulty {
+            min_moves: 12,
+            max_moves: Some(24),
+        });
+
+        let moves = solve(&board, None, &limits).unwrap();
+        assert!(distance_within(&board, moves.len() as u32, Some(moves.len() as u32)));
+        for slot in moves {
+            board.move_tile_at(slot);
+        }
+        assert!(board.is_solved());
+    }
+
+    for &(tiles_width, tiles_height) in &[(3, 2), (2, 4), (4, 2), (2, 5)] {
+        for seed in 0..5 {
+            let mut board = Puzzle::solved(tiles_width, tiles_height, seed);
+            board.scramble();
+
+            let moves = solve(&board, None, &limits).unwrap();
+            assert!(distance_within(&board, moves.len() as u32, Some(moves.len() as u32)));
+            for slot in moves {
+                board.move_tile_at(slot);
+            }
+            assert!(board.is_solved());
+        }
+    }
+
+    assert_eq!(solve(&Puzzle::solved(4, 4, 0), None, &limits), Ok(vec![]));
+}
+
+#[test]
+fn limited() {
+    let mut board = Puzzle::solved(4, 4, 3);
+    board.scramble_to(Difficulty {
+        min_moves: 30,
+        max_moves: None,
+    });
+
+    let limits = Limits {
+        node_budget: Some(100),
+        cancel: None,
+    };
+    assert_eq!(solve(&board, None, &limits), Err(SolveError::OutOfBudget));
+    assert!(!distance_within_limits(&board, 30, Some(80), &limits));
+
+    let limits = Limits {
+        node_budget: None,
+        cancel: Some(Arc::new(AtomicBool::new(true))),
+    };
+    assert_eq!(solve(&board, None, &limits), Err(SolveError::Cancelled));
+}
+
+#[test]
+fn pattern_databases() {
+    let databases = PatternDatabases::generate(3, &[vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+    let limits = Limits::default();
+
+    for seed in 0..10 {
+        let mut board = Puzzle::solved(3, 3, seed);
+        board.scramble();
+
+        // The pattern databases only change how quickly the solution is found, never its length.
+        let without = solve(&board, None, &limits).unwrap();
+        let with = solve(&board, Some(&databases), &limits).unwrap();
+        assert_eq!(with.len(), without.len());
+
+        let mut search = Search::new(&board, Some(&databases), &limits);
+        assert!(search.pattern_costs.iter().sum::<u32>() <= with.len() as u32);
+
+        for slot in with {
+            search.slide(slot as usize);
+            board.move_tile_at(slot);
+
+            let fresh = Search::new(&board, Some(&databases), &limits);
+            assert_eq!(search.pattern_costs, fresh.pattern_costs);
+        }
+        assert!(board.is_solved());
+        assert_eq!(search.estimate, 0);
+    }
+}
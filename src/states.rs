@@ -5,15 +5,34 @@ use amethyst::{
         math::{Point3, Vector2, Vector3},
         Named, Parent, Transform,
     },
-    ecs::{Entity, Join},
+    ecs::{Entities, Entity, Join, ReadStorage, WriteStorage},
     input::{is_close_requested, is_key_down, InputHandler, StringBindings},
     input::{InputEvent, VirtualKeyCode},
-    renderer::camera::Camera,
+    renderer::{camera::Camera, palette::Srgba, resources::Tint},
+    utils::application_root_dir,
     window::ScreenDimensions,
-    winit::MouseButton,
+    winit::{Event, MouseButton, WindowEvent},
 };
 
+use std::collections::VecDeque;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use fresh::constructive;
+use fresh::history::{History, Slide};
+use fresh::pdb::{self, PatternDatabases};
+use fresh::puzzle::*;
+use fresh::solver;
+use fresh::stats::{format_duration, MoveMetric, Stats};
+
+use super::animation::Easing;
 use super::board::*;
+use super::hud::Hud;
+use super::image_size;
+use super::menu::{add_text, default_font, Menu, MenuEvent};
+use super::save::{self, SavedGame};
 use crate::components::Tile;
 
 fn initialise_camera(world: &mut World, parent: Entity) -> Entity {
@@ -34,15 +53,197 @@ fn initialise_camera(world: &mut World, parent: Entity) -> Entity {
         .build()
 }
 
-pub struct Starting;
+/// Loads the cached pattern databases for boards of the given dimension, if they've been generated.
+fn load_pattern_databases(tiles_dim: u32) -> Option<PatternDatabases> {
+    let directory = application_root_dir().ok()?.join(pdb::DIRECTORY);
+    let path = PatternDatabases::path(&directory, tiles_dim);
+
+    match PatternDatabases::load(&path, tiles_dim) {
+        Ok(databases) => Some(databases),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => {
+            log::warn!("Couldn't load the pattern databases in {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// The kind of board a game is played on. Kept in the world so a finished game can start another like it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameOptions {
+    /// How many tiles wide the board is.
+    pub tiles_width: u32,
+    /// How many tiles high the board is.
+    pub tiles_height: u32,
+    /// The image the board is cut from, within the assets directory or by absolute path.
+    pub image: String,
+    /// Whether the tiles show their numbers rather than pieces of the image.
+    pub numbered: bool,
+    /// Whether new boards take the image's shape, with as many tiles along its longer side as the longer of
+    /// `tiles_width` and `tiles_height`. Otherwise an image that isn't the board's shape is cropped to it.
+    pub image_shape: bool,
+    /// How hard the board should be to solve, or `None` for a uniformly random board.
+    pub difficulty: Option<Difficulty>,
+    /// The seed to scramble the next board with, or `None` for a random one. Only used once, so playing again
+    /// doesn't give the same board.
+    pub seed: Option<u64>,
+    /// How sliding a whole line of tiles at once counts towards the player's moves.
+    pub metric: MoveMetric,
+    /// How tiles speed up and slow down as they slide.
+    pub easing: Easing,
+    /// How many times faster moves play out while the player has more queued up behind them. `1.0` plays them at the
+    /// usual speed, `f32::INFINITY` skips straight to the end.
+    pub catchup_speed: f32,
+}
+
+impl GameOptions {
+    /// The fewest tiles a side of the board can have, see `Puzzle::MIN_TILES_DIM`.
+    pub const MIN_TILES_DIM: u32 = Puzzle::MIN_TILES_DIM;
+    /// The most tiles a side of the board can have, see `Puzzle::MAX_TILES_DIM`.
+    pub const MAX_TILES_DIM: u32 = Puzzle::MAX_TILES_DIM;
+
+    /// The image to cut the board from, or `None` to number the tiles: when numbers were asked for, or when the image
+    /// isn't in the assets directory.
+    pub fn tile_image(&self) -> Option<&str> {
+        if self.numbered {
+            return None;
+        }
+
+        if image_path(&self.image).filter(|path| path.is_file()).is_none() {
+            log::warn!("Couldn't find the image {}, numbering the tiles instead", self.image);
+            return None;
+        }
+        Some(&self.image)
+    }
+
+    /// Reshapes the board to fit the image, if boards should take its shape and its size can be read. A missing image
+    /// is left for `tile_image` to report.
+    fn fit_to_image(&mut self) {
+        if !self.image_shape || self.numbered {
+            return;
+        }
+        let path = match image_path(&self.image).filter(|path| path.is_file()) {
+            Some(path) => path,
+            None => return,
+        };
+
+        match image_size::read(&path) {
+            Ok(size) => {
+                let tiles = self.tiles_width.max(self.tiles_height);
+                let (tiles_width, tiles_height) = shape_for_image(size, tiles, GameOptions::MIN_TILES_DIM);
+                self.tiles_width = tiles_width;
+                self.tiles_height = tiles_height;
+            }
+            Err(err) => log::warn!("Couldn't read the size of {}, keeping the board's shape: {}", path.display(), err),
+        }
+    }
+}
+
+/// Where games are saved, within the application root.
+fn saved_game_path() -> Option<PathBuf> {
+    application_root_dir().ok().map(|root| SavedGame::path(&root.join(save::DIRECTORY)))
+}
+
+/// Loads the game saved when the player last quit, if there is one.
+pub fn load_saved_game() -> Option<SavedGame> {
+    let path = saved_game_path().filter(|path| path.exists())?;
+
+    match SavedGame::load(&path) {
+        Ok(saved) => Some(saved),
+        Err(err) => {
+            log::warn!("Couldn't load the saved game in {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+/// Saves the game in progress, so it can be picked up again next time. Finished games aren't saved.
+fn save_game(world: &World) {
+    let board = match world.try_fetch::<Board>() {
+        Some(board) if !board.puzzle().is_solved() => board,
+        _ => return,
+    };
+    let path = match saved_game_path() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let options = world.read_resource::<GameOptions>();
+    let saved = SavedGame::new(
+        board.puzzle(),
+        &options.image,
+        options.metric,
+        &world.read_resource::<History>(),
+        &world.read_resource::<Stats>(),
+    );
+    match saved.save(&path) {
+        Ok(()) => log::info!("Saved the game to {}", path.display()),
+        Err(err) => log::warn!("Couldn't save the game to {}: {}", path.display(), err),
+    }
+}
+
+/// Deletes the saved game, once it's been finished.
+fn remove_saved_game() {
+    if let Some(path) = saved_game_path().filter(|path| path.exists()) {
+        if let Err(err) = fs::remove_file(&path) {
+            log::warn!("Couldn't delete the saved game in {}: {}", path.display(), err);
+        }
+    }
+}
+
+pub struct Starting {
+    pub options: GameOptions,
+    /// A saved game to pick up, rather than scrambling a new board.
+    pub saved: Option<SavedGame>,
+}
 
 impl SimpleState for Starting {
     fn on_start(&mut self, data: amethyst::prelude::StateData<'_, GameData<'_, '_>>) {
         let world = data.world;
         world.register::<Named>();
 
-        let board = Board::init_board(4, 600, world);
+        let screen = {
+            let dimensions = world.read_resource::<ScreenDimensions>();
+            (dimensions.width(), dimensions.height())
+        };
+        let (board, history, stats) = match self.saved.take() {
+            Some(saved) => {
+                log::info!("Continuing the saved game, scrambled with seed {}", saved.seed);
+                self.options.tiles_width = saved.tiles_width;
+                self.options.tiles_height = saved.tiles_height;
+                self.options.image = saved.image.clone();
+                self.options.metric = saved.metric;
+                let board = Board::new(saved.puzzle()).add_to_world(screen, self.options.tile_image(), world);
+                (board, saved.history.clone(), saved.stats())
+            }
+            None => {
+                let seed = self.options.seed.take().unwrap_or_else(rand::random);
+                log::info!("Scrambling the board with seed {}", seed);
+                self.options.fit_to_image();
+
+                let GameOptions { tiles_width, tiles_height, difficulty, .. } = self.options;
+                let image = self.options.tile_image();
+                let board = Board::init_board(tiles_width, tiles_height, screen, seed, difficulty, image, world);
+                (board, History::default(), Stats::default())
+            }
+        };
+        let GameOptions { tiles_width, tiles_height, .. } = self.options;
+        let hud = Hud::new(world, &stats);
+        world.insert(hud);
+        world.insert(stats);
+        world.insert(history);
+        world.insert(BufferedInput::default());
+        world.insert(self.options.clone());
         let _camera = initialise_camera(world, board);
+
+        // Games after the first keep the databases they already loaded if they're for the same size of board. There
+        // are only databases for square boards.
+        let loaded = world.try_fetch::<Arc<PatternDatabases>>().map(|it| it.dim());
+        if tiles_width == tiles_height && loaded != Some(tiles_width) {
+            if let Some(databases) = load_pattern_databases(tiles_width) {
+                world.insert(Arc::new(databases));
+            }
+        }
     }
 
     fn handle_event(
@@ -54,22 +255,280 @@ impl SimpleState for Starting {
     }
 
     fn update(&mut self, _data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
-        Trans::Switch(Box::new(Awaiting))
+        Trans::Switch(Box::new(Awaiting { hint: None, drag: None }))
+    }
+}
+
+/// How hard the optimal solver tries before falling back to the constructive solver's longer solution.
+const SOLVER_NODE_BUDGET: u64 = 200_000;
+
+/// Solves the board in the world, optimally if the solver manages it within `SOLVER_NODE_BUDGET`.
+fn find_solution(world: &World) -> Option<Vec<u32>> {
+    let board = world.read_resource::<Board>();
+    let limits = solver::Limits {
+        node_budget: Some(SOLVER_NODE_BUDGET),
+        cancel: None,
+    };
+    let databases = world.try_fetch::<Arc<PatternDatabases>>();
+
+    solver::solve(board.puzzle(), databases.as_ref().map(|it| &***it), &limits)
+        .or_else(|_| constructive::solve(board.puzzle()))
+        .ok()
+}
+
+/// Input given while a move was playing out, acted on by `Awaiting` once it's finished.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Buffered {
+    /// The slot that was clicked.
+    Click(u32),
+    Towards(Direction),
+    Undo,
+    Redo,
+}
+
+impl Buffered {
+    /// The input to buffer for an action, if it's one that makes a move.
+    fn from_action(action: &str) -> Option<Buffered> {
+        match action {
+            "undo" => Some(Buffered::Undo),
+            "redo" => Some(Buffered::Redo),
+            _ => direction(action).map(Buffered::Towards),
+        }
+    }
+}
+
+/// The inputs waiting to be acted on, oldest first. Lives in the world so it outlasts the moves it was given during.
+#[derive(Debug, Default)]
+struct BufferedInput {
+    pending: VecDeque<Buffered>,
+}
+
+impl BufferedInput {
+    /// The most inputs kept waiting. Any more are dropped, so mashing a key can't queue up moves for ages.
+    const MAX_PENDING: usize = 16;
+
+    fn push(&mut self, input: Buffered) {
+        if self.pending.len() < BufferedInput::MAX_PENDING {
+            self.pending.push_back(input);
+        }
+    }
+}
+
+/// The direction a `move_*` action slides a tile in.
+fn direction(action: &str) -> Option<Direction> {
+    match action {
+        "move_up" => Some(Direction::Up),
+        "move_down" => Some(Direction::Down),
+        "move_left" => Some(Direction::Left),
+        "move_right" => Some(Direction::Right),
+        _ => None,
+    }
+}
+
+/// A tile picked up with the mouse, being dragged towards the empty slot along with the tiles in line between them.
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    /// The slot of the tile that was picked up.
+    from: u32,
+    /// Where in the world it was picked up.
+    start: Point3<f32>,
+    /// How far the tiles have been dragged, from 0 in the slots they started in to 1 in the slots they're moving to.
+    progress: f32,
+    /// Whether the mouse has moved far enough since the tile was picked up to be a drag rather than a click.
+    dragged: bool,
+}
+
+impl Drag {
+    /// How far, as a fraction of a tile, the mouse has to move before picking up a tile counts as a drag.
+    const THRESHOLD: f32 = 0.1;
+
+    /// Follows the mouse to a new point in the world. Only movement towards the empty slot counts, and the tiles
+    /// can't be dragged past their destinations or back past where they started.
+    fn follow(&mut self, board: &Board, to: Point3<f32>) {
+        let travel = match Move::line(board, self.from) {
+            Some(tile_moves) => tile_moves[0].travel(board),
+            None => return,
+        };
+        let dragged = to - self.start;
+
+        self.progress = (dragged.dot(&travel) / travel.norm_squared()).clamp(0.0, 1.0);
+        self.dragged = self.dragged || dragged.norm() > travel.norm() * Drag::THRESHOLD;
     }
 }
 
 /// A state representing the game awaiting some input from the player. Waits until the player clicks on a tile or exits.
-struct Awaiting;
+///
+/// Tiles can also be dragged towards the empty slot. Letting go past halfway finishes the move, otherwise the tiles
+/// snap back to where they were.
+///
+/// Pressing the hint key highlights the tile the solver recommends moving next, pressing it again plays that move.
+struct Awaiting {
+    /// The slot of the tile currently highlighted as a hint, if any.
+    hint: Option<u32>,
+    /// The tile being dragged, if any.
+    drag: Option<Drag>,
+}
+
 impl Awaiting {
-    fn current_to_move(world: &World) -> Option<Move> {
+    /// Finds the slot of the tile the solver recommends moving next, or `None` if there's nothing to move.
+    fn find_hint(world: &World) -> Option<u32> {
+        find_solution(world).and_then(|moves| moves.first().copied())
+    }
+
+    /// Switches between numbered tiles and tiles cut from the image. Stays numbered if the image can't be found.
+    fn toggle_numbers(world: &mut World) {
+        {
+            let mut options = world.write_resource::<GameOptions>();
+            options.numbered = !options.numbered;
+        }
+        Awaiting::show_faces(world);
+    }
+
+    /// Cuts the tiles from a new image, cropped to the board's shape.
+    fn change_image(world: &mut World, image: String) {
+        log::info!("Cutting the tiles from {}", image);
+        {
+            let mut options = world.write_resource::<GameOptions>();
+            options.image = image;
+            options.numbered = false;
+        }
+        Awaiting::show_faces(world);
+    }
+
+    /// Shows the tiles as the `GameOptions` say, cut from the image or numbered.
+    fn show_faces(world: &mut World) {
+        let options = (*world.read_resource::<GameOptions>()).clone();
+
+        // The board has to come out of the world while it changes the tiles in the world.
+        let board = world.remove::<Board>().expect("The board should be in the world");
+        board.set_faces(world, options.tile_image());
+        world.insert(board);
+    }
+
+    /// Highlights the tile in the given slot, or clears the highlight with `None`.
+    fn highlight(world: &mut World, slot: Option<u32>) {
+        let highlighted = slot.and_then(|slot| world.read_resource::<Board>().puzzle().tile_at(slot));
+
+        world.exec(
+            |(entities, tiles, mut tints): (Entities<'_>, ReadStorage<'_, Tile>, WriteStorage<'_, Tint>)| {
+                for (entity, tile) in (&entities, &tiles).join() {
+                    if Some(tile.index) == highlighted {
+                        tints
+                            .insert(entity, Tint(Srgba::new(1.0, 0.85, 0.4, 1.0)))
+                            .expect("Tile entities should be alive");
+                    } else {
+                        tints.remove(entity);
+                    }
+                }
+            },
+        );
+    }
+
+    /// Handles the hint key: the first press highlights a tile and counts against the player's score, pressing again
+    /// while it's highlighted plays the move.
+    fn hint(&mut self, world: &mut World) -> SimpleTrans {
+        if let Some(from) = self.hint {
+            let tile_moves = Move::line(&world.read_resource::<Board>(), from)
+                .expect("A hinted tile should be next to the empty slot");
+            return Trans::Push(Box::new(ProcessingMove::new(tile_moves, MoveKind::Made)));
+        }
+
+        self.hint = Awaiting::find_hint(world);
+        if self.hint.is_some() {
+            world.write_resource::<Stats>().hints += 1;
+        }
+        Awaiting::highlight(world, self.hint);
+
+        Trans::None
+    }
+
+    /// Slides the tile that can move in the given direction, if there is one.
+    fn move_towards(world: &World, direction: Direction) -> SimpleTrans {
+        let board = world.read_resource::<Board>();
+        match board.puzzle().tile_moving(direction).and_then(|from| Move::line(&board, from)) {
+            Some(tile_moves) => Trans::Push(Box::new(ProcessingMove::new(tile_moves, MoveKind::Made))),
+            None => Trans::None,
+        }
+    }
+
+    /// Acts on input given while the last move was playing out.
+    fn apply(world: &World, input: Buffered) -> SimpleTrans {
+        match input {
+            Buffered::Click(from) => match Move::line(&world.read_resource::<Board>(), from) {
+                Some(tile_moves) => Trans::Push(Box::new(ProcessingMove::new(tile_moves, MoveKind::Made))),
+                None => Trans::None,
+            },
+            Buffered::Towards(direction) => Awaiting::move_towards(world, direction),
+            Buffered::Undo => {
+                let undo = world.read_resource::<History>().next_undo();
+                Awaiting::replay(world, undo, MoveKind::Undo)
+            }
+            Buffered::Redo => {
+                let redo = world.read_resource::<History>().next_redo();
+                Awaiting::replay(world, redo, MoveKind::Redo)
+            }
+        }
+    }
+
+    /// Plays a move from the history, if there is one.
+    fn replay(world: &World, slides: Option<Vec<Slide>>, kind: MoveKind) -> SimpleTrans {
+        match slides {
+            Some(slides) => {
+                let board = world.read_resource::<Board>();
+                let tile_moves = slides.iter().map(|slide| Move::new(&board, slide.from, slide.to)).collect();
+                Trans::Push(Box::new(ProcessingMove::new(tile_moves, kind)))
+            }
+            None => Trans::None,
+        }
+    }
+
+    /// Picks up the tile under the mouse, if it's in line with the empty slot.
+    fn pick_up(&mut self, world: &World) -> SimpleTrans {
+        self.drag = Awaiting::mouse_world_point(world).and_then(|start| {
+            let board = world.read_resource::<Board>();
+            board
+                .world_idx(start)
+                .filter(|from| Move::line(&board, *from).is_some())
+                .map(|from| Drag { from, start, progress: 0.0, dragged: false })
+        });
+
+        Trans::None
+    }
+
+    /// Lets go of the tile being dragged. A click, or a drag past halfway, plays the rest of the move. Anything less
+    /// puts the tiles back.
+    fn drop(&mut self, world: &World) -> SimpleTrans {
+        let drag = match self.drag.take() {
+            Some(drag) => drag,
+            None => return Trans::None,
+        };
+        let tile_moves = match Move::line(&world.read_resource::<Board>(), drag.from) {
+            Some(tile_moves) => tile_moves,
+            None => return Trans::None,
+        };
+
+        if !drag.dragged {
+            Trans::Push(Box::new(ProcessingMove::new(tile_moves, MoveKind::Made)))
+        } else if drag.progress >= 0.5 {
+            // Carry on from where the tiles were let go.
+            Trans::Push(Box::new(ProcessingMove {
+                start: drag.progress,
+                ..ProcessingMove::new(tile_moves, MoveKind::Made)
+            }))
+        } else {
+            place_tiles(world, &tile_moves, 0.0);
+            Trans::None
+        }
+    }
+
+    /// The point in the world under the mouse, if the mouse is over the window.
+    fn mouse_world_point(world: &World) -> Option<Point3<f32>> {
         let input = world.read_resource::<InputHandler<StringBindings>>();
         let dimensions = world.read_resource::<ScreenDimensions>();
         let cameras = world.read_storage::<Camera>();
         let transforms = world.read_storage::<Transform>();
 
-        let mouse_position = input
-            .mouse_position()
-            .expect("Mouse should be on the screen if we're detecting the keypress");
+        let mouse_position = input.mouse_position()?;
 
         let ct: (&Camera, &Transform) = (&cameras, &transforms)
             .join()
@@ -80,7 +539,7 @@ impl Awaiting {
         let dimensions = &*dimensions;
         let screen_dims = Vector2::new(dimensions.width(), dimensions.height());
 
-        let pos: Point3<f32> = camera.projection().screen_to_world_point(
+        Some(camera.projection().screen_to_world_point(
             Point3::new(
                 mouse_position.0,
                 mouse_position.1,
@@ -88,37 +547,69 @@ impl Awaiting {
             ),
             screen_dims,
             transform,
-        );
-
-        let board = world.read_resource::<Board>();
-        board
-            .world_idx(pos)
-            .filter(|from| !board.is_empty(*from))
-            .and_then(|from| {
-                board.empty_adjacent(from).map(|to| {
-                    Move::new(&*board, from, to)
-                })
-            })
+        ))
     }
 }
 
 impl SimpleState for Awaiting {
+    fn on_pause(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        // Something else moved the tiles mid-drag, put the dragged ones back first.
+        if let Some(drag) = self.drag.take() {
+            if let Some(tile_moves) = Move::line(&data.world.read_resource::<Board>(), drag.from) {
+                place_tiles(data.world, &tile_moves, 0.0);
+            }
+        }
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        // A move was made, so any hint is out of date.
+        self.hint = None;
+        Awaiting::highlight(data.world, None);
+    }
+
     fn handle_event(
         &mut self,
         data: StateData<'_, GameData<'_, '_>>,
         event: StateEvent,
     ) -> SimpleTrans {
+        if is_pause(&event) {
+            return Trans::Push(Box::new(Paused::default()));
+        }
+
+        if let Some(image) = dropped_image(&event) {
+            Awaiting::change_image(data.world, image);
+            return Trans::None;
+        }
+
         handle_common_events(data.world, &event).unwrap_or_else(|| match event {
             StateEvent::Input(input_event) => match input_event {
-                InputEvent::MouseButtonReleased(mouse_button) => match mouse_button {
-                    MouseButton::Left => {
-                        if let Some(tile_move) = Awaiting::current_to_move(data.world) {
-                            Trans::Push(Box::new(ProcessingMove { tile_move, steps_completed: 0 }))
-                        } else {
-                            Trans::None
-                        }
-                    }
-                    _ => Trans::None,
+                InputEvent::MouseButtonPressed(MouseButton::Left) => self.pick_up(data.world),
+                InputEvent::MouseButtonReleased(MouseButton::Left) => self.drop(data.world),
+                InputEvent::ActionPressed(action) if direction(&action).is_some() => {
+                    Awaiting::move_towards(data.world, direction(&action).unwrap())
+                }
+                InputEvent::ActionPressed(action) if action == "hint" => self.hint(data.world),
+                InputEvent::ActionPressed(action) if action == "cycle_easing" => {
+                    let mut options = data.world.write_resource::<GameOptions>();
+                    options.easing = options.easing.next();
+                    log::info!("Tiles now slide with {:?} easing", options.easing);
+                    Trans::None
+                }
+                InputEvent::ActionPressed(action) if action == "toggle_numbers" => {
+                    Awaiting::toggle_numbers(data.world);
+                    Trans::None
+                }
+                InputEvent::ActionPressed(action) if action == "undo" => {
+                    let undo = data.world.read_resource::<History>().next_undo();
+                    Awaiting::replay(data.world, undo, MoveKind::Undo)
+                }
+                InputEvent::ActionPressed(action) if action == "redo" => {
+                    let redo = data.world.read_resource::<History>().next_redo();
+                    Awaiting::replay(data.world, redo, MoveKind::Redo)
+                }
+                InputEvent::ActionPressed(action) if action == "solve" => match find_solution(data.world) {
+                    Some(moves) => Trans::Push(Box::new(Playback::new(moves))),
+                    None => Trans::None,
                 },
                 _ => Trans::None,
             },
@@ -130,10 +621,27 @@ impl SimpleState for Awaiting {
         &mut self,
         StateData { world, .. }: &mut StateData<'_, GameData<'_, '_>>,
     ) -> SimpleTrans {
-        if world.read_resource::<Board>().is_solved() {
-            Trans::Replace(Box::new(Winner {}))
-        } else {
-            Trans::None
+        if let Some(drag) = self.drag.as_mut() {
+            if let Some(to) = Awaiting::mouse_world_point(world) {
+                drag.follow(&world.read_resource::<Board>(), to);
+            }
+            if let Some(tile_moves) = Move::line(&world.read_resource::<Board>(), drag.from) {
+                place_tiles(world, &tile_moves, drag.progress);
+            }
+        }
+
+        if world.read_resource::<Board>().puzzle().is_solved() {
+            return Trans::Replace(Box::new(Winner::default()));
+        }
+
+        // Inputs given during the last move wait for the player to finish dragging.
+        let buffered = match self.drag {
+            Some(_) => None,
+            None => world.write_resource::<BufferedInput>().pending.pop_front(),
+        };
+        match buffered {
+            Some(input) => Awaiting::apply(world, input),
+            None => Trans::None,
         }
     }
 }
@@ -146,27 +654,100 @@ struct Move {
     to: u32,
     /// The id of the tile being moved.
     tile: TileId,
-    move_step: Vector3<f32>,
 }
 
 impl Move {
-    const NUM_STEPS: u32 = 10;
+    /// How long a move takes to play out, in seconds.
+    const DURATION: f32 = 0.15;
 
     fn new(board: &Board, from: u32, to: u32) -> Self {
-        let tile = board.tile_at(from).unwrap();
-        let f_pos = board.idx_world(from as i32).unwrap();
-        let t_pos = board.idx_world(to as i32).unwrap();
+        let tile = board.puzzle().tile_at(from).unwrap();
 
-        let move_step: Vector3<f32> = (t_pos - f_pos).scale(1.0 / Move::NUM_STEPS as f32);
+        Move { from, to, tile }
+    }
 
-        Move { from, to, tile, move_step }
+    /// How far, and which way, the tile moves in the world. Worked out from the board each time, so it stays right
+    /// when the board is resized.
+    fn travel(&self, board: &Board) -> Vector3<f32> {
+        let from = board.idx_world(self.from as i32).expect("A move should start on the board");
+        let to = board.idx_world(self.to as i32).expect("A move should end on the board");
+        to - from
+    }
+
+    /// The moves sliding the line of tiles from `from` up to the empty slot along by one, nearest the empty slot first,
+    /// if `from` is in line with the empty slot.
+    fn line(board: &Board, from: u32) -> Option<Vec<Move>> {
+        let slots = board.puzzle().line_to_empty(from)?;
+
+        // Each tile takes the slot of the one ahead of it, the first one the empty slot.
+        let targets = std::iter::once(board.puzzle().empty_slot()).chain(slots.iter().copied());
+        Some(slots.iter().zip(targets).map(|(from, to)| Move::new(board, *from, to)).collect())
     }
 }
 
+/// Puts the tiles making up a move the given fraction of the way along it.
+fn place_tiles(world: &World, tile_moves: &[Move], progress: f32) {
+    let board = world.read_resource::<Board>();
+    let tiles = world.read_storage::<Tile>();
+    let mut transforms = world.write_storage::<Transform>();
+
+    for (tile, transform) in (&tiles, &mut transforms).join() {
+        if let Some(tile_move) = tile_moves.iter().find(|it| it.tile == tile.index) {
+            let from = board.tile_transform(tile_move.from);
+            transform.set_translation(from.translation() + tile_move.travel(&board) * progress);
+        }
+    }
+}
+
+/// Where a move being played out came from, which decides how it's recorded in the `History`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MoveKind {
+    /// A new move, by the player or the solver.
+    Made,
+    /// Undoing the last move made.
+    Undo,
+    /// Redoing the last move undone.
+    Redo,
+}
+
 /// A state representing the game playing out a move, no input except exiting is accepted..
+///
+/// A move slides a line of one or more tiles together, given nearest the empty slot first. The tiles take
+/// `Move::DURATION` to get there whatever the frame rate, eased by the game's `Easing`.
+///
+/// Clicks and moves given while the player's moves play out are buffered to be made once they're done.
 struct ProcessingMove {
-    tile_move: Move,
-    steps_completed: u32,
+    tile_moves: Vec<Move>,
+    kind: MoveKind,
+    /// Whether input is buffered, rather than thrown away. Solutions being played back don't take any.
+    buffering: bool,
+    /// How far along the move the tiles already were when it started, from 0 to 1. Only a dragged tile starts partway.
+    start: f32,
+    /// Seconds since the move started.
+    elapsed: f32,
+}
+
+impl ProcessingMove {
+    fn new(tile_moves: Vec<Move>, kind: MoveKind) -> Self {
+        ProcessingMove { tile_moves, kind, buffering: true, start: 0.0, elapsed: 0.0 }
+    }
+
+    /// How long the move takes in seconds. The rest of a move takes the rest of the time, so a dragged tile carries on
+    /// at the same pace.
+    fn duration(&self) -> f32 {
+        Move::DURATION * (1.0 - self.start)
+    }
+
+    /// Moves the move on by `delta` seconds, played `speed` times faster, returning whether the tiles have arrived. An
+    /// infinite speed finishes the move straight away, even on a frame that took no time.
+    fn advance(&mut self, delta: f32, speed: f32) -> bool {
+        if speed.is_infinite() {
+            self.elapsed = self.duration();
+        } else {
+            self.elapsed += delta * speed;
+        }
+        self.elapsed >= self.duration()
+    }
 }
 
 impl SimpleState for ProcessingMove {
@@ -175,42 +756,278 @@ impl SimpleState for ProcessingMove {
         data: StateData<'_, GameData<'_, '_>>,
         event: StateEvent,
     ) -> SimpleTrans {
-        // The only input we care about in this state is the common stuff.
-        handle_common_events(data.world, &event).unwrap_or(Trans::None)
+        if is_pause(&event) {
+            return Trans::Push(Box::new(Paused::default()));
+        }
+
+        handle_common_events(data.world, &event).unwrap_or_else(|| {
+            if !self.buffering {
+                return Trans::None;
+            }
+
+            let input = match event {
+                StateEvent::Input(InputEvent::MouseButtonReleased(MouseButton::Left)) => {
+                    Awaiting::mouse_world_point(data.world)
+                        .and_then(|point| data.world.read_resource::<Board>().world_idx(point))
+                        .map(Buffered::Click)
+                }
+                StateEvent::Input(InputEvent::ActionPressed(action)) => Buffered::from_action(&action),
+                _ => None,
+            };
+            if let Some(input) = input {
+                data.world.write_resource::<BufferedInput>().push(input);
+            }
+
+            Trans::None
+        })
     }
 
     fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
-        if self.steps_completed >= Move::NUM_STEPS {
-            // Tile has arrived, pop back to awaiting input state.
-            data.world.fetch_mut::<Board>().move_tile_at(self.tile_move.from);
-
-            Trans::Pop
+        let speed = if data.world.read_resource::<BufferedInput>().pending.is_empty() {
+            1.0
         } else {
-            // Tile hasn't arrived yet, remain in this state.
+            data.world.read_resource::<GameOptions>().catchup_speed
+        };
+        let delta = data.world.read_resource::<Time>().delta_seconds();
+
+        if self.advance(delta, speed) {
+            // Tiles have arrived, pop back to awaiting input state.
+            let arrived: Vec<(TileId, Transform)> = {
+                let mut board = data.world.fetch_mut::<Board>();
+                for tile_move in &self.tile_moves {
+                    board.puzzle_mut().move_tile_at(tile_move.from);
+                }
+                self.tile_moves
+                    .iter()
+                    .map(|tile_move| (tile_move.tile, board.tile_transform(tile_move.to)))
+                    .collect()
+            };
+
+            // Snap the tiles exactly into their slots, in case the board was resized while they were moving.
             let tiles = data.world.read_component::<Tile>();
             let mut transforms = data.world.write_component::<Transform>();
+            for (tile, transform) in (&tiles, &mut transforms).join() {
+                if let Some((_, arrived)) = arrived.iter().find(|(id, _)| *id == tile.index) {
+                    *transform = arrived.clone();
+                }
+            }
+
+            let slides = self
+                .tile_moves
+                .iter()
+                .map(|tile_move| Slide {
+                    from: tile_move.from,
+                    to: tile_move.to,
+                    tile: tile_move.tile,
+                })
+                .collect();
+            let moves = data.world.read_resource::<GameOptions>().metric.count(self.tile_moves.len());
+            let mut history = data.world.write_resource::<History>();
+            let mut stats = data.world.write_resource::<Stats>();
+            match self.kind {
+                MoveKind::Made => {
+                    history.record(slides);
+                    stats.moves += moves;
+                    stats.timing = true;
+                }
+                MoveKind::Undo => {
+                    history.undo();
+                    stats.moves = stats.moves.saturating_sub(moves);
+                }
+                MoveKind::Redo => {
+                    history.redo();
+                    stats.moves += moves;
+                }
+            }
+
+            Trans::Pop
+        } else {
+            // Tiles haven't arrived yet, remain in this state.
+            let eased = data.world.read_resource::<GameOptions>().easing.apply(self.elapsed / self.duration());
+            place_tiles(data.world, &self.tile_moves, self.start + (1.0 - self.start) * eased);
+
+            Trans::None
+        }
+    }
+}
 
-            for tt in (&tiles, &mut transforms).join() {
-                let (tile, transform): (&Tile, &mut Transform) = tt;
+/// A state playing a solution out on the board a move at a time through `ProcessingMove`, for showing the game off.
+///
+/// Playback can be paused, stepped through a move at a time while paused, and sped up or slowed down. Pressing the
+/// solve key again stops it and hands the board back to the player.
+struct Playback {
+    /// The moves still to play, as slots to hand to `Puzzle::move_tile_at`.
+    moves: VecDeque<u32>,
+    paused: bool,
+    /// Moves played per second.
+    speed: f32,
+    /// Seconds left until the next move is played.
+    wait: f32,
+}
+
+impl Playback {
+    const DEFAULT_SPEED: f32 = 2.0;
+    const MIN_SPEED: f32 = 0.25;
+    const MAX_SPEED: f32 = 16.0;
+
+    fn new(moves: Vec<u32>) -> Self {
+        Playback {
+            moves: moves.into(),
+            paused: false,
+            speed: Playback::DEFAULT_SPEED,
+            wait: 0.0,
+        }
+    }
+
+    /// Starts playing the next move, or stops playback once there are none left.
+    fn play_next(&mut self, world: &World) -> SimpleTrans {
+        let from = match self.moves.pop_front() {
+            Some(from) => from,
+            None => return Trans::Pop,
+        };
+        self.wait = 1.0 / self.speed;
 
-                if tile.index == self.tile_move.tile {
-                    transform.append_translation(self.tile_move.move_step.clone());
-                    self.steps_completed += 1;
+        let tile_moves = Move::line(&world.read_resource::<Board>(), from)
+            .expect("A solution's moves should be next to the empty slot");
+        Trans::Push(Box::new(ProcessingMove {
+            buffering: false,
+            ..ProcessingMove::new(tile_moves, MoveKind::Made)
+        }))
+    }
+}
 
-                    break;
+impl SimpleState for Playback {
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if is_pause(&event) {
+            return Trans::Push(Box::new(Paused::default()));
+        }
+
+        handle_common_events(data.world, &event).unwrap_or_else(|| match event {
+            StateEvent::Input(InputEvent::ActionPressed(action)) => match action.as_str() {
+                "solve" => Trans::Pop,
+                "playback_pause" => {
+                    self.paused = !self.paused;
+                    Trans::None
                 }
-            }
+                "playback_step" if self.paused => self.play_next(data.world),
+                "playback_faster" => {
+                    self.speed = (self.speed * 2.0).min(Playback::MAX_SPEED);
+                    Trans::None
+                }
+                "playback_slower" => {
+                    self.speed = (self.speed / 2.0).max(Playback::MIN_SPEED);
+                    Trans::None
+                }
+                _ => Trans::None,
+            },
+            _ => Trans::None,
+        })
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        if self.paused {
+            return Trans::None;
+        }
 
+        self.wait -= data.world.read_resource::<Time>().delta_seconds();
+        if self.wait <= 0.0 {
+            self.play_next(data.world)
+        } else {
             Trans::None
         }
     }
 }
 
-struct Winner {}
+/// What the player can do once they've won.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WinnerChoice {
+    PlayAgain,
+    Smaller,
+    Bigger,
+    /// Play again, counting moves the other way.
+    SwitchMetric,
+    MainMenu,
+    Quit,
+}
+
+/// A state showing off the completed image along with how the player did, until they choose to play again or quit.
+///
+/// Each choice can be picked from the menu, or with the key named next to it.
+#[derive(Default)]
+struct Winner {
+    menu: Menu<WinnerChoice>,
+}
+
+impl Winner {
+    fn choose(&self, world: &World, choice: WinnerChoice) -> SimpleTrans {
+        let mut options = (*world.read_resource::<GameOptions>()).clone();
+        let resize = |tiles: u32| match choice {
+            WinnerChoice::Smaller => (tiles - 1).max(GameOptions::MIN_TILES_DIM),
+            WinnerChoice::Bigger => (tiles + 1).min(GameOptions::MAX_TILES_DIM),
+            _ => tiles,
+        };
+        match choice {
+            WinnerChoice::Quit => return Trans::Quit,
+            WinnerChoice::MainMenu => return Trans::Switch(Box::new(MainMenu::new(options, None))),
+            WinnerChoice::SwitchMetric => options.metric = options.metric.switched(),
+            _ => {
+                options.tiles_width = resize(options.tiles_width);
+                options.tiles_height = resize(options.tiles_height);
+            }
+        }
+
+        Trans::Switch(Box::new(Starting { options, saved: None }))
+    }
+}
+
 impl SimpleState for Winner {
-    fn on_start(&mut self, _data: StateData<'_, GameData<'_, '_>>) {
-        print!("Ye win");
-        unimplemented!()
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        remove_saved_game();
+
+        // The board has to come out of the world while it adds a tile to the world.
+        let board = world.remove::<Board>().expect("The board should be in the world");
+        board.complete_image(world);
+        world.insert(board);
+
+        let (summary, score) = {
+            let mut stats = world.write_resource::<Stats>();
+            stats.timing = false;
+            (
+                format!("Solved in {} moves and {}", stats.moves, format_duration(stats.elapsed)),
+                format!("Score {} ({} hints)", stats.score(), stats.hints),
+            )
+        };
+        log::info!("{}, {}", summary, score);
+
+        let font = default_font(world);
+        add_text(world, &font, "Solved!".to_string(), 48.0, 200.0);
+        add_text(world, &font, summary, 24.0, 140.0);
+        add_text(world, &font, score, 24.0, 110.0);
+
+        let switch_metric = match world.read_resource::<GameOptions>().metric {
+            MoveMetric::Tiles => "Count lines as one move (M)",
+            MoveMetric::Lines => "Count every tile moved (M)",
+        };
+        let choices = vec![
+            ("Play again (R)", WinnerChoice::PlayAgain),
+            ("Smaller board ([)", WinnerChoice::Smaller),
+            ("Bigger board (])", WinnerChoice::Bigger),
+            (switch_metric, WinnerChoice::SwitchMetric),
+            ("Main menu", WinnerChoice::MainMenu),
+            ("Quit (Esc)", WinnerChoice::Quit),
+        ];
+        let choices = choices.into_iter().map(|(text, choice)| (text.to_string(), choice)).collect();
+        self.menu = Menu::new(world, &font, choices, -120.0);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        // Starting sets up everything again for the next game.
+        data.world.delete_all();
     }
 
     fn handle_event(
@@ -218,8 +1035,353 @@ impl SimpleState for Winner {
         data: StateData<'_, GameData<'_, '_>>,
         event: StateEvent,
     ) -> SimpleTrans {
-        // The only input we care about in this state is the common stuff.
-        handle_common_events(data.world, &event).unwrap_or(Trans::None)
+        handle_common_events(data.world, &event).unwrap_or_else(|| {
+            let choice = match &event {
+                StateEvent::Input(InputEvent::ActionPressed(action)) => match action.as_str() {
+                    "play_again" => Some(WinnerChoice::PlayAgain),
+                    "smaller_board" => Some(WinnerChoice::Smaller),
+                    "bigger_board" => Some(WinnerChoice::Bigger),
+                    "switch_metric" => Some(WinnerChoice::SwitchMetric),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let choice = choice.or_else(|| match self.menu.handle_event(data.world, &event) {
+                Some(MenuEvent::Picked(choice)) => Some(choice),
+                _ => None,
+            });
+
+            match choice {
+                Some(choice) => self.choose(data.world, choice),
+                None => Trans::None,
+            }
+        })
+    }
+}
+
+/// The settings on the main menu, and what else it offers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MainMenuItem {
+    Continue,
+    Width,
+    Height,
+    Image,
+    Numbered,
+    Shape,
+    Difficulty,
+    Metric,
+    Start,
+    Quit,
+}
+
+/// Whether the file's a kind of image the board can be cut from, going by its extension.
+fn is_image(path: &Path) -> bool {
+    let extension = path.extension().and_then(|it| it.to_str()).map(str::to_lowercase);
+    matches!(extension.as_deref(), Some("jpg") | Some("jpeg") | Some("png"))
+}
+
+/// The path of the image file dropped onto the window, if that's what the event is.
+fn dropped_image(event: &StateEvent) -> Option<String> {
+    match event {
+        StateEvent::Window(Event::WindowEvent { event: WindowEvent::DroppedFile(path), .. }) if is_image(path) => {
+            path.to_str().map(str::to_string)
+        }
+        _ => None,
+    }
+}
+
+/// The image files in the assets directory, by name, for the board to be cut from.
+fn find_images() -> Vec<String> {
+    let directory = match application_root_dir() {
+        Ok(root) => root.join("assets"),
+        Err(_) => return vec![],
+    };
+    let entries = match fs::read_dir(&directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::warn!("Couldn't look for images in {}: {}", directory.display(), err);
+            return vec![];
+        }
+    };
+
+    let mut images: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| is_image(path))
+        .filter_map(|path| path.file_name().and_then(|it| it.to_str()).map(str::to_string))
+        .collect();
+    images.sort();
+    images
+}
+
+/// A state for setting up a game before it starts: the board's size and image, how hard it's scrambled and how
+/// moves are counted. A game saved when the player last quit can be continued from here too.
+///
+/// Settings are changed by clicking them, or selecting them and pressing left or right.
+pub struct MainMenu {
+    options: GameOptions,
+    saved: Option<SavedGame>,
+    /// The images the board can be cut from.
+    images: Vec<String>,
+    menu: Menu<MainMenuItem>,
+}
+
+impl MainMenu {
+    pub fn new(options: GameOptions, saved: Option<SavedGame>) -> Self {
+        let mut images = find_images();
+        if !images.contains(&options.image) {
+            images.insert(0, options.image.clone());
+        }
+
+        MainMenu {
+            options,
+            saved,
+            images,
+            menu: Menu::default(),
+        }
+    }
+
+    fn label(&self, item: MainMenuItem) -> String {
+        let options = &self.options;
+        match item {
+            MainMenuItem::Continue => match &self.saved {
+                Some(saved) => format!("Continue {}x{} game", saved.tiles_width, saved.tiles_height),
+                None => String::new(),
+            },
+            MainMenuItem::Width => format!("Width: {}", options.tiles_width),
+            MainMenuItem::Height => format!("Height: {}", options.tiles_height),
+            MainMenuItem::Image => {
+                // Images from outside the assets directory are shown without the directories they're in.
+                let path = Path::new(&options.image);
+                format!("Image: {}", path.file_name().and_then(|it| it.to_str()).unwrap_or(&options.image))
+            }
+            MainMenuItem::Numbered if options.numbered => "Tiles: numbered".to_string(),
+            MainMenuItem::Numbered => "Tiles: cut from the image".to_string(),
+            MainMenuItem::Shape if options.image_shape => "Board shape: fits the image".to_string(),
+            MainMenuItem::Shape => "Board shape: as set".to_string(),
+            MainMenuItem::Difficulty => {
+                let (name, _) = Difficulty::NAMED
+                    .iter()
+                    .find(|(_, difficulty)| *difficulty == options.difficulty)
+                    .unwrap_or(&("Custom", None));
+                format!("Difficulty: {}", name)
+            }
+            MainMenuItem::Metric => match options.metric {
+                MoveMetric::Tiles => "Moves counted: every tile".to_string(),
+                MoveMetric::Lines => "Moves counted: every line".to_string(),
+            },
+            MainMenuItem::Start => "Start".to_string(),
+            MainMenuItem::Quit => "Quit (Esc)".to_string(),
+        }
+    }
+
+    /// Changes a setting by `step`, going round the choices where there are only a few.
+    fn adjust(&mut self, item: MainMenuItem, step: i32) {
+        let options = &mut self.options;
+        let next = |idx: Option<usize>, len: usize| (idx.unwrap_or(0) as i32 + step).rem_euclid(len as i32) as usize;
+        let resize = |tiles: u32| {
+            (tiles as i32 + step).clamp(GameOptions::MIN_TILES_DIM as i32, GameOptions::MAX_TILES_DIM as i32) as u32
+        };
+
+        match item {
+            MainMenuItem::Width => options.tiles_width = resize(options.tiles_width),
+            MainMenuItem::Height => options.tiles_height = resize(options.tiles_height),
+            MainMenuItem::Image => {
+                let idx = self.images.iter().position(|it| *it == options.image);
+                options.image = self.images[next(idx, self.images.len())].clone();
+            }
+            MainMenuItem::Difficulty => {
+                let idx = Difficulty::NAMED.iter().position(|(_, it)| *it == options.difficulty);
+                options.difficulty = Difficulty::NAMED[next(idx, Difficulty::NAMED.len())].1;
+            }
+            MainMenuItem::Numbered => options.numbered = !options.numbered,
+            MainMenuItem::Shape => options.image_shape = !options.image_shape,
+            MainMenuItem::Metric => options.metric = options.metric.switched(),
+            MainMenuItem::Continue | MainMenuItem::Start | MainMenuItem::Quit => {}
+        }
+    }
+}
+
+impl SimpleState for MainMenu {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+
+        let font = default_font(world);
+        add_text(world, &font, "Sliding tiles".to_string(), 48.0, 200.0);
+
+        let mut items = vec![
+            MainMenuItem::Width,
+            MainMenuItem::Height,
+            MainMenuItem::Image,
+            MainMenuItem::Numbered,
+            MainMenuItem::Shape,
+            MainMenuItem::Difficulty,
+            MainMenuItem::Metric,
+            MainMenuItem::Start,
+            MainMenuItem::Quit,
+        ];
+        if self.saved.is_some() {
+            items.insert(0, MainMenuItem::Continue);
+        }
+        let items = items.into_iter().map(|item| (self.label(item), item)).collect();
+        self.menu = Menu::new(world, &font, items, 120.0);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        data.world.delete_all();
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let Some(image) = dropped_image(&event) {
+            if !self.images.contains(&image) {
+                self.images.push(image.clone());
+            }
+            self.options.image = image;
+            self.options.numbered = false;
+            for item in [MainMenuItem::Image, MainMenuItem::Numbered].iter() {
+                self.menu.set_text(data.world, *item, self.label(*item));
+            }
+            return Trans::None;
+        }
+
+        handle_common_events(data.world, &event).unwrap_or_else(|| {
+            let (item, step) = match self.menu.handle_event(data.world, &event) {
+                Some(MenuEvent::Picked(MainMenuItem::Continue)) => {
+                    let saved = self.saved.take();
+                    return Trans::Switch(Box::new(Starting { options: self.options.clone(), saved }));
+                }
+                Some(MenuEvent::Picked(MainMenuItem::Start)) => {
+                    return Trans::Switch(Box::new(Starting { options: self.options.clone(), saved: None }));
+                }
+                Some(MenuEvent::Picked(MainMenuItem::Quit)) => return Trans::Quit,
+                Some(MenuEvent::Picked(item)) => (item, 1),
+                Some(MenuEvent::Adjusted(item, step)) => (item, step),
+                None => return Trans::None,
+            };
+
+            self.adjust(item, step);
+            self.menu.set_text(data.world, item, self.label(item));
+            Trans::None
+        })
+    }
+}
+
+/// What the player can do with a paused game.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PauseChoice {
+    Resume,
+    /// Start a new board like this one.
+    Restart,
+    /// Save the game, and go back to the main menu.
+    MainMenu,
+    Quit,
+}
+
+/// Takes the game out of the world: its entities, and its resources, so nothing carries on running or saving it.
+/// Pattern databases are kept for the next game.
+fn end_game(world: &mut World) {
+    world.delete_all();
+    world.remove::<Board>();
+    world.remove::<Stats>();
+    world.remove::<History>();
+    world.remove::<Hud>();
+    world.remove::<BufferedInput>();
+}
+
+/// Whether the event is the player pausing or resuming the game.
+fn is_pause(event: &StateEvent) -> bool {
+    match event {
+        StateEvent::Window(event) => is_key_down(event, VirtualKeyCode::Escape),
+        StateEvent::Input(InputEvent::ActionPressed(action)) => action == "pause",
+        _ => false,
+    }
+}
+
+/// A state pausing the game, with a menu over the board to resume, restart or quit it. The clock doesn't run while
+/// the game's paused, and neither do any moves or playback underneath.
+#[derive(Default)]
+struct Paused {
+    /// The "Paused" title above the menu.
+    title: Option<Entity>,
+    menu: Menu<PauseChoice>,
+    /// Whether the clock was running before the game was paused.
+    timing: bool,
+}
+
+impl Paused {
+    /// Goes back to the game, with the clock running again if it was before.
+    fn resume(&self, world: &mut World) -> SimpleTrans {
+        world.write_resource::<Stats>().timing = self.timing;
+        Trans::Pop
+    }
+
+    fn choose(&self, world: &mut World, choice: PauseChoice) -> SimpleTrans {
+        let options = (*world.read_resource::<GameOptions>()).clone();
+        // The game's states are still on the stack under the pause menu, and go along with it.
+        match choice {
+            PauseChoice::Resume => self.resume(world),
+            PauseChoice::Restart => {
+                end_game(world);
+                Trans::NewStack(vec![Box::new(Starting { options, saved: None })])
+            }
+            PauseChoice::MainMenu => {
+                save_game(world);
+                end_game(world);
+                Trans::NewStack(vec![Box::new(MainMenu::new(options, load_saved_game()))])
+            }
+            PauseChoice::Quit => {
+                save_game(world);
+                Trans::Quit
+            }
+        }
+    }
+}
+
+impl SimpleState for Paused {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        {
+            let mut stats = world.write_resource::<Stats>();
+            self.timing = stats.timing;
+            stats.timing = false;
+        }
+
+        let font = default_font(world);
+        self.title = Some(add_text(world, &font, "Paused".to_string(), 48.0, 120.0));
+        let choices = vec![
+            ("Resume (Esc)".to_string(), PauseChoice::Resume),
+            ("Restart".to_string(), PauseChoice::Restart),
+            ("Main menu".to_string(), PauseChoice::MainMenu),
+            ("Quit".to_string(), PauseChoice::Quit),
+        ];
+        self.menu = Menu::new(world, &font, choices, 40.0);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        let world = data.world;
+        self.menu.delete(world);
+        if let Some(title) = self.title.take().filter(|it| world.is_alive(*it)) {
+            world.delete_entity(title).expect("The title is alive");
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if is_pause(&event) {
+            return self.resume(data.world);
+        }
+
+        handle_common_events(data.world, &event).unwrap_or_else(|| match self.menu.handle_event(data.world, &event) {
+            Some(MenuEvent::Picked(choice)) => self.choose(data.world, choice),
+            _ => Trans::None,
+        })
     }
 }
 
@@ -229,10 +1391,20 @@ fn handle_common_events<T>(world: &mut World, event: &StateEvent) -> Option<Tran
     match event {
         StateEvent::Window(event) => {
             if is_close_requested(event) || is_key_down(event, VirtualKeyCode::Escape) {
-                // Player wants to exit.
+                // Player wants to exit, they can pick up where they left off next time.
+                save_game(world);
                 Some(Trans::Quit)
-            } else if is_key_down(&event, VirtualKeyCode::Space) {
-                // Debugging: print the name and transform of all named and transformy entities.
+            } else if let Event::WindowEvent { event: WindowEvent::Focused(focused), .. } = event {
+                // The clock doesn't run while the player's looking at something else.
+                if let Some(mut stats) = world.try_fetch_mut::<Stats>() {
+                    stats.paused = !focused;
+                }
+
+                None
+            } else if is_key_down(event, VirtualKeyCode::Space) {
+                // Debugging: print the name and transform of all named and transformy entities, then the board. There's
+                // no board until a game has started.
+                let board = format!("{:?}", &*world.try_fetch::<Board>()?);
                 world.exec(
                     |(named, transforms): (ReadStorage<'_, Named>, ReadStorage<'_, Transform>)| {
                         for (name, transform) in (&named, &transforms).join() {
@@ -241,8 +1413,7 @@ fn handle_common_events<T>(world: &mut World, event: &StateEvent) -> Option<Tran
                     },
                 );
 
-                let board = world.read_resource::<Board>();
-                println!("Board => {:?}", &*board);
+                println!("Board => {}", board);
 
                 None
             } else {
@@ -252,3 +1423,20 @@ fn handle_common_events<T>(world: &mut World, event: &StateEvent) -> Option<Tran
         _ => None,
     }
 }
+
+#[test]
+fn advance_move() {
+    let mut processing = ProcessingMove::new(vec![], MoveKind::Made);
+    assert!(!processing.advance(Move::DURATION / 2.0, 1.0));
+    assert!(processing.advance(Move::DURATION / 2.0, 1.0));
+
+    // Catching up at infinite speed finishes straight away, even on a frame that took no time.
+    let mut processing = ProcessingMove::new(vec![], MoveKind::Made);
+    assert!(processing.advance(0.0, f32::INFINITY));
+    assert_eq!(processing.elapsed, Move::DURATION);
+
+    let mut processing = ProcessingMove::new(vec![], MoveKind::Made);
+    processing.start = 0.5;
+    assert!(!processing.advance(0.0, 3.0));
+    assert!(processing.advance(Move::DURATION / 6.0, 3.0));
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How moves are counted when a line of tiles slides at once.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MoveMetric {
+    /// Every tile that slides counts as a move.
+    Tiles,
+    /// Sliding a line of tiles counts as a single move, however long it is.
+    Lines,
+}
+
+impl MoveMetric {
+    /// How many moves sliding the given number of tiles at once counts as.
+    pub fn count(self, tiles: usize) -> u32 {
+        match self {
+            MoveMetric::Tiles => tiles as u32,
+            MoveMetric::Lines => 1,
+        }
+    }
+
+    /// The other way of counting moves.
+    pub fn switched(self) -> MoveMetric {
+        match self {
+            MoveMetric::Tiles => MoveMetric::Lines,
+            MoveMetric::Lines => MoveMetric::Tiles,
+        }
+    }
+}
+
+/// How the player is getting on with the current board.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Stats {
+    /// Moves made so far, counted by the game's `MoveMetric`.
+    pub moves: u32,
+    /// Hints asked for so far.
+    pub hints: u32,
+    /// Time spent playing so far, counted by `tick`.
+    pub elapsed: Duration,
+    /// Whether the clock is running, which it does from the first move until the board's solved.
+    pub timing: bool,
+    /// Whether the clock is held while the window doesn't have focus.
+    pub paused: bool,
+}
+
+impl Stats {
+    /// How many moves each hint is worth when scoring.
+    pub const HINT_PENALTY: u32 = 5;
+
+    /// The player's score, lower is better: the moves they've made plus a penalty for every hint they've used.
+    pub fn score(&self) -> u32 {
+        self.moves + self.hints * Stats::HINT_PENALTY
+    }
+
+    /// Counts the time passed since the last tick towards the time spent playing, if the clock's running and not
+    /// paused.
+    pub fn tick(&mut self, delta: Duration) {
+        if self.timing && !self.paused {
+            self.elapsed += delta;
+        }
+    }
+
+    /// How quickly the player's been making moves.
+    pub fn moves_per_second(&self) -> f32 {
+        let seconds = self.elapsed.as_secs_f32();
+        if seconds > 0.0 {
+            self.moves as f32 / seconds
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Formats a duration as minutes and seconds, eg. `3:07`.
+pub fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[test]
+fn metric() {
+    assert_eq!(MoveMetric::Tiles.count(1), 1);
+    assert_eq!(MoveMetric::Tiles.count(3), 3);
+    assert_eq!(MoveMetric::Lines.count(1), 1);
+    assert_eq!(MoveMetric::Lines.count(3), 1);
+    assert_eq!(MoveMetric::Tiles.switched(), MoveMetric::Lines);
+    assert_eq!(MoveMetric::Lines.switched(), MoveMetric::Tiles);
+}
+
+#[test]
+fn score() {
+    assert_eq!(Stats::default().score(), 0);
+    assert_eq!(Stats { moves: 12, ..Stats::default() }.score(), 12);
+    assert_eq!(Stats { moves: 12, hints: 2, ..Stats::default() }.score(), 12 + 2 * Stats::HINT_PENALTY);
+}
+
+#[test]
+fn clock() {
+    let second = Duration::from_secs(1);
+    let mut stats = Stats::default();
+
+    // Nothing counts until the first move.
+    stats.tick(second);
+    assert_eq!(stats.elapsed, Duration::from_secs(0));
+    assert_eq!(stats.moves_per_second(), 0.0);
+
+    stats.timing = true;
+    stats.moves = 6;
+    stats.tick(second);
+    stats.paused = true;
+    stats.tick(second);
+    stats.paused = false;
+    stats.tick(second);
+    assert_eq!(stats.elapsed, 2 * second);
+    assert_eq!(stats.moves_per_second(), 3.0);
+}
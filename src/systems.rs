@@ -0,0 +1,115 @@
+use amethyst::{
+    core::{Time, Transform},
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, Write, WriteStorage},
+    renderer::camera::Camera,
+    ui::{UiText, UiTransform},
+    window::ScreenDimensions,
+};
+
+use fresh::stats::Stats;
+
+use super::board::{Board, NumberStyle};
+use super::components::{Tile, TileNumber};
+use super::hud::Hud;
+
+/// Keeps the board fitted to the window. Whenever the screen's dimensions change the board is resized, every tile is
+/// moved and scaled to match, and the camera is given a projection covering the new screen so that clicks still land
+/// on the right slots.
+#[derive(Default)]
+pub struct FitBoardSystem {
+    /// The screen dimensions the board was last fitted to.
+    fitted: Option<(f32, f32)>,
+}
+
+impl<'s> System<'s> for FitBoardSystem {
+    type SystemData = (
+        ReadExpect<'s, ScreenDimensions>,
+        Option<Write<'s, Board>>,
+        ReadStorage<'s, Tile>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, Camera>,
+    );
+
+    fn run(&mut self, (screen, board, tiles, mut transforms, mut cameras): Self::SystemData) {
+        let mut board = match board {
+            Some(board) => board,
+            None => return,
+        };
+
+        let size = (screen.width(), screen.height());
+        if self.fitted == Some(size) {
+            return;
+        }
+        self.fitted = Some(size);
+
+        board.fit(size.0, size.1);
+        for (tile, transform) in (&tiles, &mut transforms).join() {
+            // The only tile that isn't on the board is the one filling the empty slot once it's solved.
+            let slot = board.puzzle().tile_slot(tile.index).unwrap_or(0);
+            *transform = board.tile_transform(slot);
+        }
+
+        for camera in (&mut cameras).join() {
+            *camera = Camera::standard_2d(size.0, size.1);
+        }
+    }
+}
+
+/// Runs the player's clock, and keeps the `Hud` showing their latest stats.
+pub struct HudSystem;
+
+impl<'s> System<'s> for HudSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        Option<Write<'s, Stats>>,
+        Option<Read<'s, Hud>>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (time, stats, hud, mut texts): Self::SystemData) {
+        let mut stats = match stats {
+            Some(stats) => stats,
+            None => return,
+        };
+        stats.tick(time.delta_time());
+
+        if let Some(hud) = hud {
+            for (entity, line) in hud.lines.iter().zip(Hud::lines(&stats)) {
+                // A new game deletes the old overlay before it adds its own.
+                if let Some(text) = texts.get_mut(*entity) {
+                    text.text = line;
+                }
+            }
+        }
+    }
+}
+
+/// Keeps the numbers on numbered tiles over their tiles, as they slide and as the board's resized. The camera's centred
+/// on the board, so a tile's place on the board is also its place from the middle of the screen.
+pub struct TileNumberSystem;
+
+impl<'s> System<'s> for TileNumberSystem {
+    type SystemData = (
+        Read<'s, NumberStyle>,
+        ReadStorage<'s, TileNumber>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, UiTransform>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (style, numbers, transforms, mut ui_transforms, mut texts): Self::SystemData) {
+        for (number, ui_transform, text) in (&numbers, &mut ui_transforms, &mut texts).join() {
+            let transform = match transforms.get(number.tile) {
+                Some(transform) => transform,
+                None => continue,
+            };
+
+            let size = transform.scale().x;
+            ui_transform.local_x = transform.translation().x;
+            ui_transform.local_y = transform.translation().y;
+            ui_transform.width = size;
+            ui_transform.height = size;
+            text.font_size = size * style.text_size;
+        }
+    }
+}